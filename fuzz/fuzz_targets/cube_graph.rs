@@ -0,0 +1,16 @@
+#![no_main]
+
+use graph_generator::comm::CubeGraph;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|graph: CubeGraph| {
+    let edges = graph.build();
+    assert_eq!(edges.len(), graph.edge_count());
+    assert_eq!(graph.timestep_partition().len(), graph.timesteps());
+    for ts in 0..graph.timesteps() {
+        graph.window_edges(ts, ts + 1);
+    }
+    graph.build_undirected();
+    graph.build_adjacency();
+    graph.build_with_stats();
+});
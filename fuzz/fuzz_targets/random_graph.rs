@@ -0,0 +1,15 @@
+#![no_main]
+
+use graph_generator::random::RandomGraph;
+use graph_generator::util::is_acyclic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|graph: RandomGraph| {
+    let edges = graph.build_edges();
+    let n_nodes = edges
+        .iter()
+        .map(|(tail, head)| *tail.max(head) as usize + 1)
+        .max()
+        .unwrap_or(0);
+    assert!(is_acyclic(&edges, n_nodes));
+});
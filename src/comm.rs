@@ -1,46 +1,134 @@
-use std::collections::HashSet;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use super::error::GraphError;
+use super::graph::GraphStats;
+use super::util::Lcg;
 
 pub fn comp_graph(inside: usize, outside: usize, n_layers: usize) -> Vec<(usize, usize)> {
-    if n_layers <= 1 || inside + outside == 0 {
-        return Vec::new();
-    }
+    CompGraph::new(inside, outside, n_layers).build()
+}
 
-    let layers = create_layers(inside + outside, n_layers);
-    let mut edges = Vec::new();
+/// Determines how the outside vertices of a [`CompGraph`] are connected through comm vertices.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommTopology {
+    /// Every outside column gets its own comm vertex between two layers,
+    /// connecting the upper vertex of the column only to the lower vertex of the same column.
+    Matched,
+    /// There is a single comm vertex between two layers, which receives from every outside
+    /// vertex of the upper layer and sends to every outside vertex of the lower layer.
+    /// This is the default, and is what [`comp_graph`] creates.
+    Broadcast,
+}
 
-    // add neighbor edges
-    for (upper, lower) in layers.iter().zip(&layers[1..]) {
-        for i in 0..inside + outside {
-            let vertex = upper[i];
-            // add left neighbor
-            if let Some(left) = lower.get(i.wrapping_sub(1)) {
-                edges.push((vertex, *left));
-            }
-            // add lower neighbor
-            edges.push((vertex, lower[i]));
-            // add right neibhbor
-            if let Some(right) = lower.get(i + 1) {
-                edges.push((vertex, *right));
-            }
+/// Determines which kinds of edges a [`CompGraph`] contains.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NeighborMode {
+    /// Both the neighbor edges and the edges of the comm vertices. This is the default.
+    Full,
+    /// Only the edges from the outside vertices to the comm vertices and back.
+    CommOnly,
+    /// Only the edges between neighbors in consecutive layers.
+    NeighborOnly,
+}
+
+/// A graph consisting of `n_layers` layers, with `inside + outside` vertices each.
+/// Every vertex is connected to its lower, left and right neighbor in the next layer.
+/// The `outside` vertices, which are the last ones of every layer, are additionally
+/// connected to the next layer through comm vertices.
+pub struct CompGraph {
+    inside: usize,
+    outside: usize,
+    n_layers: usize,
+    topology: CommTopology,
+    neighbor_mode: NeighborMode,
+}
+
+impl CompGraph {
+    pub fn new(inside: usize, outside: usize, n_layers: usize) -> Self {
+        Self {
+            inside,
+            outside,
+            n_layers,
+            topology: CommTopology::Broadcast,
+            neighbor_mode: NeighborMode::Full,
         }
     }
 
-    if outside == 0 {
-        return edges;
+    /// Set how the comm vertices are connected.
+    pub fn with_topology(mut self, topology: CommTopology) -> Self {
+        self.topology = topology;
+        self
     }
 
-    // add comm edges
-    let mut comm = (inside + outside) * n_layers;
+    /// Set which kinds of edges are created.
+    pub fn with_neighbor_mode(mut self, neighbor_mode: NeighborMode) -> Self {
+        self.neighbor_mode = neighbor_mode;
+        self
+    }
 
-    for (upper, lower) in layers.iter().zip(&layers[1..]) {
-        for (vertex_upper, vertex_lower) in upper[inside..].iter().zip(&lower[inside..]) {
-            edges.push((*vertex_upper, comm));
-            edges.push((comm, *vertex_lower));
+    /// Get the layer of the vertex `id`, where the first layer is `0`.
+    ///
+    /// Returns `None` for comm vertices, which lie between two layers,
+    /// and for ids which are not in the graph.
+    pub fn layer_of(&self, id: usize) -> Option<usize> {
+        let nodes_per_layer = self.inside + self.outside;
+        if nodes_per_layer == 0 {
+            return None;
         }
-        comm += 1;
+        let layer = id / nodes_per_layer;
+        (layer < self.n_layers).then_some(layer)
     }
 
-    edges
+    pub fn build(&self) -> Vec<(usize, usize)> {
+        let (inside, outside, n_layers) = (self.inside, self.outside, self.n_layers);
+        if n_layers <= 1 || inside + outside == 0 {
+            return Vec::new();
+        }
+
+        let layers = create_layers(inside + outside, n_layers);
+        let mut edges = Vec::new();
+
+        // add neighbor edges
+        if self.neighbor_mode != NeighborMode::CommOnly {
+            for (upper, lower) in layers.iter().zip(&layers[1..]) {
+                for i in 0..inside + outside {
+                    let vertex = upper[i];
+                    // add left neighbor
+                    if let Some(left) = lower.get(i.wrapping_sub(1)) {
+                        edges.push((vertex, *left));
+                    }
+                    // add lower neighbor
+                    edges.push((vertex, lower[i]));
+                    // add right neibhbor
+                    if let Some(right) = lower.get(i + 1) {
+                        edges.push((vertex, *right));
+                    }
+                }
+            }
+        }
+
+        if outside == 0 || self.neighbor_mode == NeighborMode::NeighborOnly {
+            return edges;
+        }
+
+        // add comm edges
+        let first_comm = (inside + outside) * n_layers;
+
+        for (gap, (upper, lower)) in layers.iter().zip(&layers[1..]).enumerate() {
+            let columns = upper[inside..].iter().zip(&lower[inside..]);
+            for (column, (vertex_upper, vertex_lower)) in columns.enumerate() {
+                let comm = match self.topology {
+                    CommTopology::Matched => first_comm + gap * outside + column,
+                    CommTopology::Broadcast => first_comm + gap,
+                };
+                edges.push((*vertex_upper, comm));
+                edges.push((comm, *vertex_lower));
+            }
+        }
+
+        edges
+    }
 }
 
 #[inline(always)]
@@ -52,77 +140,717 @@ fn create_layers(nodes_per_layer: usize, n_layers: usize) -> Vec<Vec<usize>> {
 
 type Cube = Vec<Vec<Vec<usize>>>;
 
+/// Determines which neighbors a cell of a [`CubeGraph`] is connected to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Neighborhood {
+    /// Cells are only connected to their neighbors in the next timestep.
+    /// There are no edges within a timestep. This is the default.
+    Temporal,
+    /// Cells are additionally connected to their neighbors in the same timestep.
+    /// To keep the graph acyclic, these edges always go from the lower to the higher id.
+    SpatialTemporal,
+}
+
+/// Determines which cells of the next timestep a cell of a [`CubeGraph`] is coupled to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CouplingPattern {
+    /// The 26 cells surrounding the counterpart of the cell. This is the default.
+    Moore,
+    /// Only the cells at the given `(di, dj, dk)` offsets from the counterpart of the cell.
+    /// Offsets which leave the cube are skipped.
+    Offsets(Vec<(isize, isize, isize)>),
+}
+
+/// The direction of the link between an outer cell and the same cell in the next timestep,
+/// which goes through the comm vertex.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TemporalDir {
+    /// The link goes from timestep `t` to `t + 1`. This is the default.
+    Forward,
+    /// The link goes from timestep `t + 1` back to `t`.
+    Backward,
+}
+
+/// The position of a cell of a [`CubeGraph`] relative to the boundary of its cube.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoundaryClass {
+    /// The cell is not an outer cell.
+    Interior,
+    /// The cell lies on the boundary in one dimension.
+    Face,
+    /// The cell lies on the boundary in two dimensions.
+    Edge,
+    /// The cell lies on the boundary in all three dimensions.
+    Corner,
+}
+
+#[derive(Debug)]
 pub struct CubeGraph {
     cubes: Vec<Cube>,
-    width: usize,
-    height: usize,
-    depth: usize,
+    dims: Vec<(usize, usize, usize)>,
     timesteps: usize,
+    neighborhood: Neighborhood,
+    coupling: CouplingPattern,
+    temporal_dir: fn((usize, usize, usize, usize)) -> TemporalDir,
+    comm_id_base: Option<usize>,
 }
 
 impl CubeGraph {
     pub fn new(width: usize, height: usize, depth: usize, timesteps: usize) -> Self {
+        Self::new_variable(vec![(width, height, depth); timesteps])
+    }
+
+    /// Creates a graph like [`new`](CubeGraph::new), but checks the dimensions first.
+    ///
+    /// Returns [`GraphError::InvalidDimension`] if any of them is `0`,
+    /// and [`GraphError::Overflow`] if the ids of the nodes do not fit into a `usize`.
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        depth: usize,
+        timesteps: usize,
+    ) -> Result<Self, GraphError> {
+        let dimensions = [
+            ("width", width),
+            ("height", height),
+            ("depth", depth),
+            ("timesteps", timesteps),
+        ];
+        if let Some((name, value)) = dimensions.into_iter().find(|(_, value)| *value == 0) {
+            return Err(GraphError::InvalidDimension { name, value });
+        }
+        // check before allocating the cubes, the same as `validate`
+        width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(depth))
+            .and_then(|n| n.checked_mul(timesteps))
+            .and_then(|n| n.checked_add(timesteps - 1))
+            .ok_or(GraphError::Overflow)?;
+
+        Ok(Self::new(width, height, depth, timesteps))
+    }
+
+    /// Creates a graph where every timestep can have its own `(width, height, depth)`.
+    ///
+    /// If the dimensions of two consecutive timesteps differ, a cell `(x, y, z)` is linked
+    /// to the cell of the next timestep with the nearest relative position, so in every
+    /// dimension `x` becomes `round(x * (next_width - 1) / (width - 1))`.
+    /// Its neighbors in the next timestep are the ones surrounding that cell,
+    /// and outer cells are linked to it through the comm vertex.
+    /// Cells of a finer timestep, which are not the nearest cell of any cell of the
+    /// previous timestep, don't receive a link from the comm vertex.
+    /// A timestep with a size of `0` in any dimension has no cells, so the cells of the
+    /// timestep before it are not linked to the next timestep at all.
+    pub fn new_variable(dims_per_timestep: Vec<(usize, usize, usize)>) -> Self {
         let mut id = 0;
         let mut cubes = Vec::new();
-        for t in 0..timesteps {
+        for &(width, height, depth) in &dims_per_timestep {
             let mut cube = vec![vec![vec![0; depth]; height];width];
+            for plane in cube.iter_mut() {
+                for row in plane.iter_mut() {
+                    for cell in row.iter_mut() {
+                        *cell = id;
+                        id += 1;
+                    }
+                }
+            }
+            cubes.push(cube);
+        }
+        Self {
+            cubes,
+            timesteps: dims_per_timestep.len(),
+            dims: dims_per_timestep,
+            neighborhood: Neighborhood::Temporal,
+            coupling: CouplingPattern::Moore,
+            temporal_dir: |_| TemporalDir::Forward,
+            comm_id_base: None,
+        }
+    }
+
+    /// Set which neighbors the cells are connected to.
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// Set which cells of the next timestep the cells are connected to.
+    /// The links through the comm vertices are not affected.
+    pub fn with_coupling(mut self, coupling: CouplingPattern) -> Self {
+        self.coupling = coupling;
+        self
+    }
+
+    /// Set the direction of the link through the comm vertex for every outer cell,
+    /// given its coordinates `(x, y, z, ts)`, where `ts` is the earlier of the two timesteps.
+    ///
+    /// Note that the graph is no longer guaranteed to be acyclic if any link goes backward.
+    pub fn with_temporal_dir(
+        mut self,
+        temporal_dir: fn((usize, usize, usize, usize)) -> TemporalDir,
+    ) -> Self {
+        self.temporal_dir = temporal_dir;
+        self
+    }
+
+    /// Number the comm vertices starting at `base`, instead of right after the cells.
+    /// The comm vertex between timestep `ts` and `ts + 1` gets the id `base + ts`.
+    ///
+    /// Panics if `base` is less than the number of cells, since the ids would collide.
+    pub fn with_comm_id_base(mut self, base: usize) -> Self {
+        assert!(
+            base >= self.cell_count(),
+            "comm id base {} collides with the ids of the {} cells",
+            base,
+            self.cell_count()
+        );
+        self.comm_id_base = Some(base);
+        self
+    }
+
+    pub fn build(&self) -> Vec<(usize, usize)> {
+        self.window_edges(0, self.timesteps)
+    }
+
+    /// Build the edges, like [`build`](CubeGraph::build), and summarize them while they are
+    /// built, instead of traversing them again.
+    ///
+    /// The ids skipped with [`with_comm_id_base`](CubeGraph::with_comm_id_base) are not nodes,
+    /// so they are not considered for the degrees.
+    pub fn build_with_stats(&self) -> (Vec<(usize, usize)>, GraphStats) {
+        let n_cells = self.cell_count();
+        let first_comm_id = self.first_comm_id();
+        // the comm vertices follow the cells directly
+        let index = |id: usize| id.checked_sub(first_comm_id).map_or(id, |gap| n_cells + gap);
+
+        let mut out_degrees = vec![0; self.node_count()];
+        let mut in_degrees = vec![0; self.node_count()];
+        let edges = self.window_edges_filtered(0, self.timesteps, &mut |(tail, head)| {
+            out_degrees[index(tail)] += 1;
+            in_degrees[index(head)] += 1;
+            true
+        });
+        let stats = GraphStats::from_degrees(edges.len(), &out_degrees, &in_degrees);
+
+        (edges, stats)
+    }
+
+    /// Build the successors and the predecessors of every node as `(out_adj, in_adj)`,
+    /// indexed by the id of the node, while the edges are created.
+    ///
+    /// Both have an entry for every id up to the highest one, so with
+    /// [`with_comm_id_base`](CubeGraph::with_comm_id_base) the skipped ids have empty entries.
+    #[allow(clippy::type_complexity)]
+    pub fn build_adjacency(&self) -> (Vec<Vec<u32>>, Vec<Vec<u32>>) {
+        let n_ids = self.first_comm_id() + self.timesteps.saturating_sub(1);
+        let mut out_adj = vec![Vec::new(); n_ids];
+        let mut in_adj = vec![Vec::new(); n_ids];
+        self.window_edges_filtered(0, self.timesteps, &mut |(tail, head)| {
+            out_adj[tail].push(head as u32);
+            in_adj[head].push(tail as u32);
+            // the edges themselves are not needed
+            false
+        });
+
+        (out_adj, in_adj)
+    }
+
+    /// Build the undirected adjacency of the graph, which connects all neighbors within a
+    /// timestep, regardless of the [`Neighborhood`], as well as the links to the next
+    /// timestep and through the comm vertices.
+    ///
+    /// Every adjacency is emitted once, as a pair `(a, b)` with `a < b`, and the pairs are sorted.
+    pub fn build_undirected(&self) -> Vec<(usize, usize)> {
+        let mut pairs = BTreeSet::new();
+        let mut push = |(tail, head): (usize, usize)| {
+            pairs.insert((tail.min(head), tail.max(head)));
+        };
+        self.build().into_iter().for_each(&mut push);
+        if self.neighborhood == Neighborhood::Temporal {
+            self.spatial_edges(0, self.timesteps, &mut push);
+        }
+
+        pairs.into_iter().collect()
+    }
+
+    /// Build the edges in a pseudo random order, which only depends on `seed`.
+    /// The edges are the same as the ones of [`build`](CubeGraph::build).
+    pub fn build_shuffled(&self, seed: u64) -> Vec<(usize, usize)> {
+        let mut edges = self.build();
+        Lcg::with_seed(seed).shuffle(&mut edges);
+        edges
+    }
+
+    /// Build the edges with a weight given by `weight_fn` for the coordinates
+    /// `(x, y, z, ts)` of the tail and the head.
+    ///
+    /// Comm vertices have no position, they get the coordinates
+    /// `(usize::MAX, usize::MAX, usize::MAX, ts)`, where `ts` is the earlier of the two timesteps.
+    pub fn build_weighted_with(
+        &self,
+        weight_fn: impl Fn((usize, usize, usize, usize), (usize, usize, usize, usize)) -> f64,
+    ) -> Vec<(usize, usize, f64)> {
+        let coordinate = self.coordinate_lookup();
+        self.build()
+            .into_iter()
+            .map(|(tail, head)| (tail, head, weight_fn(coordinate(tail), coordinate(head))))
+            .collect()
+    }
+
+    /// Build the edges with the weight `comm_w` for the edges of the comm vertices,
+    /// and `spatial_w` for all edges between two cells.
+    pub fn build_weighted_split(&self, spatial_w: f64, comm_w: f64) -> Vec<(usize, usize, f64)> {
+        let first_comm_id = self.first_comm_id();
+        self.build()
+            .into_iter()
+            .map(|(tail, head)| {
+                let is_comm_edge = tail >= first_comm_id || head >= first_comm_id;
+                (tail, head, if is_comm_edge { comm_w } else { spatial_w })
+            })
+            .collect()
+    }
+
+    /// Build the edges for which `keep` returns true, given the coordinates `(x, y, z, ts)`
+    /// of the tail and the head. Edges are filtered while they are created.
+    ///
+    /// Comm vertices get the coordinates `(usize::MAX, usize::MAX, usize::MAX, ts)`,
+    /// like in [`build_weighted_with`](CubeGraph::build_weighted_with).
+    pub fn build_filtered(
+        &self,
+        keep: impl Fn((usize, usize, usize, usize), (usize, usize, usize, usize)) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let coordinate = self.coordinate_lookup();
+        self.window_edges_filtered(0, self.timesteps, &mut |(tail, head)| {
+            keep(coordinate(tail), coordinate(head))
+        })
+    }
+
+    /// Returns a function, which maps the id of a node to its coordinates `(x, y, z, ts)`,
+    /// using `(usize::MAX, usize::MAX, usize::MAX, ts)` for comm vertices.
+    fn coordinate_lookup(&self) -> impl Fn(usize) -> (usize, usize, usize, usize) {
+        let first_comm_id = self.first_comm_id();
+        let cells = self.coordinates();
+        move |id| match id.checked_sub(first_comm_id) {
+            Some(ts) => (usize::MAX, usize::MAX, usize::MAX, ts),
+            None => cells[id],
+        }
+    }
+
+    /// The coordinates `(x, y, z, ts)` of every cell, indexed by its id.
+    fn coordinates(&self) -> Vec<(usize, usize, usize, usize)> {
+        let mut coordinates = Vec::with_capacity(self.cell_count());
+        for (ts, &(width, height, depth)) in self.dims.iter().enumerate() {
             for x in 0..width {
                 for y in 0..height {
                     for z in 0..depth {
-                        cube[x][y][z] = id;
-                        id += 1;
+                        coordinates.push((x, y, z, ts));
                     }
                 }
             }
-            cubes.push(cube);
         }
-        Self { cubes, width, height, depth, timesteps }
+
+        coordinates
+    }
+
+    /// The width of the cube of the first timestep, or `0` if there are no timesteps.
+    /// With [`new_variable`](CubeGraph::new_variable) later timesteps may differ.
+    pub fn width(&self) -> usize {
+        self.dims.first().map_or(0, |dims| dims.0)
+    }
+
+    /// The height of the cube of the first timestep, or `0` if there are no timesteps.
+    pub fn height(&self) -> usize {
+        self.dims.first().map_or(0, |dims| dims.1)
+    }
+
+    /// The depth of the cube of the first timestep, or `0` if there are no timesteps.
+    pub fn depth(&self) -> usize {
+        self.dims.first().map_or(0, |dims| dims.2)
+    }
+
+    /// The number of timesteps.
+    pub fn timesteps(&self) -> usize {
+        self.timesteps
+    }
+
+    /// The number of nodes in the graph, which are all cells and one comm vertex
+    /// between every two timesteps.
+    ///
+    /// With [`with_comm_id_base`](CubeGraph::with_comm_id_base) the ids are not contiguous,
+    /// so they are not all less than the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.cell_count() + self.timesteps.saturating_sub(1)
+    }
+
+    /// The number of edges of the graph, computed without building it.
+    pub fn edge_count(&self) -> usize {
+        self.comm_edge_count() + self.neighbor_edge_count()
+    }
+
+    /// The number of edges from and to comm vertices, which are two for every outer cell
+    /// of every timestep except the last one, unless the next timestep has no cells.
+    pub fn comm_edge_count(&self) -> usize {
+        self.dims
+            .iter()
+            .zip(self.dims.iter().skip(1))
+            .map(|(&(w, h, d), &(nw, nh, nd))| {
+                if nw * nh * nd == 0 {
+                    return 0;
+                }
+                let inner = w.saturating_sub(2) * h.saturating_sub(2) * d.saturating_sub(2);
+                2 * (w * h * d - inner)
+            })
+            .sum()
+    }
+
+    /// The number of edges between cells, which are the edges to the neighbors in the next
+    /// timestep and, with [`Neighborhood::SpatialTemporal`], the ones within a timestep.
+    pub fn neighbor_edge_count(&self) -> usize {
+        // the number of cells in a 3x3x3 block around every cell is the product of the
+        // neighbors in every dimension, so the sum over all cells can be split up by dimension
+        let block_size = |c: usize, size: usize| 1 + (c > 0) as usize + (c + 1 < size) as usize;
+        let blocks_per_dimension = |size: usize, next_size: usize| -> usize {
+            (0..size)
+                .map(|c| block_size(scale_coordinate(c, size, next_size), next_size))
+                .sum()
+        };
+        let blocks = |(w, h, d): (usize, usize, usize), (nw, nh, nd): (usize, usize, usize)| {
+            blocks_per_dimension(w, nw) * blocks_per_dimension(h, nh) * blocks_per_dimension(d, nd)
+        };
+
+        // the same holds for the cells which have a neighbor at a given offset
+        let hits_per_dimension = |size: usize, next_size: usize, offset: isize| -> usize {
+            (0..size)
+                .filter(|c| {
+                    scale_coordinate(*c, size, next_size)
+                        .checked_add_signed(offset)
+                        .is_some_and(|n| n < next_size)
+                })
+                .count()
+        };
+
+        let cells = |(w, h, d): (usize, usize, usize)| w * h * d;
+        let temporal = self
+            .dims
+            .iter()
+            .zip(self.dims.iter().skip(1))
+            .map(|(dims, next_dims)| match &self.coupling {
+                _ if cells(*next_dims) == 0 => 0,
+                CouplingPattern::Moore => blocks(*dims, *next_dims) - cells(*dims),
+                CouplingPattern::Offsets(offsets) => {
+                    let ((w, h, d), (nw, nh, nd)) = (*dims, *next_dims);
+                    offsets
+                        .iter()
+                        .map(|&(di, dj, dk)| {
+                            hits_per_dimension(w, nw, di)
+                                * hits_per_dimension(h, nh, dj)
+                                * hits_per_dimension(d, nd, dk)
+                        })
+                        .sum()
+                }
+            })
+            .sum::<usize>();
+        let spatial = match self.neighborhood {
+            Neighborhood::Temporal => 0,
+            // every pair of neighbors is connected once
+            Neighborhood::SpatialTemporal => self
+                .dims
+                .iter()
+                .map(|dims| (blocks(*dims, *dims) - cells(*dims)) / 2)
+                .sum(),
+        };
+
+        temporal + spatial
+    }
+
+    /// Partition the nodes by timestep. The group of timestep `ts` contains its cells,
+    /// followed by the comm vertex between `ts` and `ts + 1`, if there is one.
+    pub fn timestep_partition(&self) -> Vec<Vec<u32>> {
+        let first_comm_id = self.first_comm_id();
+        let mut first_id = 0;
+        (0..self.timesteps)
+            .map(|ts| {
+                let (width, height, depth) = self.dims[ts];
+                let n_cells = width * height * depth;
+                let mut group = (first_id..first_id + n_cells)
+                    .map(|id| id as u32)
+                    .collect::<Vec<_>>();
+                if ts + 1 < self.timesteps {
+                    group.push((first_comm_id + ts) as u32);
+                }
+                first_id += n_cells;
+                group
+            })
+            .collect()
+    }
+
+    /// Get the id of the comm vertex, which the cell `(x, y, z)` of timestep `ts` is linked to
+    /// the next timestep through.
+    ///
+    /// Returns `None` if the cell is not an outer cell, does not exist,
+    /// or `ts` is the last timestep, or the next timestep has no cells.
+    pub fn comm_vertex_for(&self, x: usize, y: usize, z: usize, ts: usize) -> Option<usize> {
+        let (width, height, depth) = *self.dims.get(ts)?;
+        if ts + 1 >= self.timesteps || x >= width || y >= height || z >= depth {
+            return None;
+        }
+        self.counterpart(x, y, z, ts)?;
+        self.is_outer_at(x, y, z, ts)
+            .then(|| self.first_comm_id() + ts)
+    }
+
+    /// Classify the cell `(x, y, z)` of the first timestep by the number of dimensions,
+    /// in which it lies on the boundary of the cube.
+    ///
+    /// Returns `None` if the cell does not exist, which is always the case without timesteps.
+    pub fn boundary_class(&self, x: usize, y: usize, z: usize) -> Option<BoundaryClass> {
+        let (width, height, depth) = *self.dims.first()?;
+        if x >= width || y >= height || z >= depth {
+            return None;
+        }
+        let on_boundary = [(x, width), (y, height), (z, depth)]
+            .into_iter()
+            .filter(|(c, size)| *c == 0 || *c + 1 == *size)
+            .count();
+        Some(match on_boundary {
+            0 => BoundaryClass::Interior,
+            1 => BoundaryClass::Face,
+            2 => BoundaryClass::Edge,
+            _ => BoundaryClass::Corner,
+        })
+    }
+
+    /// The number of cells over all timesteps, which is also the id of the first comm vertex
+    /// if there is no comm id base.
+    fn cell_count(&self) -> usize {
+        self.dims.iter().map(|(w, h, d)| w * h * d).sum()
+    }
+
+    fn first_comm_id(&self) -> usize {
+        self.comm_id_base.unwrap_or_else(|| self.cell_count())
     }
 
-    pub fn build(self) -> Vec<(usize, usize)> {
+    /// Checks that the ids of all nodes fit into a `usize`, so the ids of the comm vertices
+    /// can not collide with the ids of the cells.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        let n_cells = self
+            .dims
+            .iter()
+            .try_fold(0usize, |n_cells, (w, h, d)| {
+                w.checked_mul(*h)
+                    .and_then(|n| n.checked_mul(*d))
+                    .and_then(|n| n.checked_add(n_cells))
+            })
+            .ok_or(GraphError::Overflow)?;
+        // the first comm id is at least n_cells, so it is larger than every cell id
+        self.comm_id_base
+            .unwrap_or(n_cells)
+            .checked_add(self.timesteps.saturating_sub(1))
+            .ok_or(GraphError::Overflow)?;
+        Ok(())
+    }
+
+    /// Write the edges to a text file, in the same format as [`write_to_file`](crate::write_to_file),
+    /// with a `# timestep N` header before the edges of every gap between timestep `N` and `N + 1`.
+    ///
+    /// Spatial edges of the last timestep are written in the section of the last gap.
+    #[cfg(feature = "std")]
+    pub fn write_sectioned(&self, filename: &str) -> Result<(), GraphError> {
+        let mut buffer = String::new();
+        for ts in 0..self.timesteps {
+            if ts + 1 < self.timesteps {
+                buffer.push_str(&format!("# timestep {}\n", ts));
+            }
+            self.window_edges(ts, ts + 1)
+                .into_iter()
+                .for_each(|(tail, head)| buffer.push_str(&format!("{} -> {}\n", tail, head)));
+        }
+
+        Ok(std::fs::write(filename, buffer)?)
+    }
+
+    /// Write the position of every node to a text file, as one line `id x y z ts` per cell.
+    ///
+    /// Comm vertices have no position, their lines are `id - - - ts`,
+    /// where `ts` is the timestep before the comm vertex.
+    #[cfg(feature = "std")]
+    pub fn write_coords(&self, filename: &str) -> Result<(), GraphError> {
+        let mut buffer = String::new();
+        for (id, (x, y, z, ts)) in self.coordinates().into_iter().enumerate() {
+            buffer.push_str(&format!("{} {} {} {} {}\n", id, x, y, z, ts));
+        }
+        let gaps = 0..self.timesteps.saturating_sub(1);
+        for (ts, id) in gaps.zip(self.first_comm_id()..) {
+            buffer.push_str(&format!("{} - - - {}\n", id, ts));
+        }
+
+        Ok(std::fs::write(filename, buffer)?)
+    }
+
+    /// Get the coordinates `(x, y, z, ts)` of the cell `id`.
+    ///
+    /// Returns `None` for comm vertices and for ids which are not in the graph.
+    pub fn coords(&self, id: usize) -> Option<(usize, usize, usize, usize)> {
+        let mut first_id = 0;
+        for (ts, &(width, height, depth)) in self.dims.iter().enumerate() {
+            let n_cells = width * height * depth;
+            if id < first_id + n_cells {
+                let offset = id - first_id;
+                let (x, rest) = (offset / (height * depth), offset % (height * depth));
+                return Some((x, rest / depth, rest % depth, ts));
+            }
+            first_id += n_cells;
+        }
+        None
+    }
+
+    /// Build only the edges which originate in a timestep in `start..end`.
+    ///
+    /// An edge between two cells originates in the timestep of its tail, and all edges of the
+    /// comm vertex between timestep `ts` and `ts + 1` originate in `ts`, so the comm vertex
+    /// between `end - 1` and `end` is included. With [`TemporalDir::Backward`], the link
+    /// through it starts at a cell of timestep `end`.
+    /// The windows of consecutive ranges split the edges of [`build`](CubeGraph::build).
+    pub fn window_edges(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        self.window_edges_filtered(start, end, &mut |_| true)
+    }
+
+    /// Build the edges which originate in a timestep in `start..end`, like
+    /// [`window_edges`](CubeGraph::window_edges), as long as `keep` returns true for them.
+    fn window_edges_filtered(
+        &self,
+        start: usize,
+        end: usize,
+        keep: &mut dyn FnMut((usize, usize)) -> bool,
+    ) -> Vec<(usize, usize)> {
+        debug_assert!(self.validate().is_ok());
         let mut edges = Vec::new();
-        let mut comm_id = self.width * self.height * self.depth * self.timesteps;
+        let mut push = |edge| {
+            if keep(edge) {
+                edges.push(edge);
+            }
+        };
+        let first_comm_id = self.first_comm_id();
+        let end = end.min(self.timesteps);
 
-        for ts in 0..(self.timesteps - 1) {
-            for x in 0..self.width {
-                for y in 0..self.height {
-                    for z in 0..self.depth {
+        let gaps = start..end.min(self.timesteps.saturating_sub(1));
+        for (ts, comm_id) in gaps.clone().zip(first_comm_id + gaps.start..) {
+            let (width, height, depth) = self.dims[ts];
+            for x in 0..width {
+                for y in 0..height {
+                    for z in 0..depth {
                         let cur = self.cubes[ts][x][y][z];
                         self.get_neighbors(x, y, z, ts)
                             .into_iter()
                             .map(|n| (cur, n))
-                            .for_each(|e| edges.push(e));
+                            .for_each(&mut push);
                         
-                        if self.is_outer_vertex(x, y, z) {
-                            edges.push((cur, comm_id));
-                            edges.push((comm_id, self.cubes[ts + 1][x][y][z]));
+                        let next = self
+                            .counterpart(x, y, z, ts)
+                            .map(|(nx, ny, nz)| self.cubes[ts + 1][nx][ny][nz]);
+                        if let Some(next) = next.filter(|_| self.is_outer_at(x, y, z, ts)) {
+                            match (self.temporal_dir)((x, y, z, ts)) {
+                                TemporalDir::Forward => {
+                                    push((cur, comm_id));
+                                    push((comm_id, next));
+                                }
+                                TemporalDir::Backward => {
+                                    push((next, comm_id));
+                                    push((comm_id, cur));
+                                }
+                            }
                         }
                     }
                 }
             }
-            comm_id += 1;
+        }
+
+        if self.neighborhood == Neighborhood::SpatialTemporal {
+            self.spatial_edges(start, end, &mut push);
         }
 
         edges
     }
 
+    /// Emit the edges between neighbors in the same timestep, for every timestep in
+    /// `start..end`. Every pair of neighbors is emitted once, from the lower to the higher id.
+    fn spatial_edges(&self, start: usize, end: usize, push: &mut dyn FnMut((usize, usize))) {
+        for ts in start..end {
+            let (width, height, depth) = self.dims[ts];
+            for x in 0..width {
+                for y in 0..height {
+                    for z in 0..depth {
+                        let cur = self.cubes[ts][x][y][z];
+                        self.get_neighbors_at(x, y, z, ts)
+                            .into_iter()
+                            .filter(|n| *n > cur)
+                            .for_each(|n| push((cur, n)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the neighbors of a cell in the next timestep, which are the cells
+    /// surrounding its counterpart, as selected by the [`CouplingPattern`].
     fn get_neighbors(&self, x: usize, y: usize, z: usize, ts: usize) -> Vec<usize> {
+        if ts + 1 >= self.timesteps {
+            return Vec::new();
+        }
+        let Some((x, y, z)) = self.counterpart(x, y, z, ts) else {
+            return Vec::new();
+        };
+        match &self.coupling {
+            CouplingPattern::Moore => self.get_neighbors_at(x, y, z, ts + 1),
+            CouplingPattern::Offsets(offsets) => offsets
+                .iter()
+                .filter_map(|&(di, dj, dk)| {
+                    self.cubes[ts + 1]
+                        .get(x.wrapping_add_signed(di))
+                        .and_then(|yy| yy.get(y.wrapping_add_signed(dj)))
+                        .and_then(|zz| zz.get(z.wrapping_add_signed(dk)))
+                        .copied()
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the coordinates of the cell in the next timestep, which is nearest
+    /// to the relative position of the cell in timestep `ts`.
+    /// Returns `None` if the next timestep has no cells.
+    fn counterpart(
+        &self,
+        x: usize,
+        y: usize,
+        z: usize,
+        ts: usize,
+    ) -> Option<(usize, usize, usize)> {
+        let (width, height, depth) = self.dims[ts];
+        let (next_width, next_height, next_depth) = self.dims[ts + 1];
+        if next_width == 0 || next_height == 0 || next_depth == 0 {
+            return None;
+        }
+        Some((
+            scale_coordinate(x, width, next_width),
+            scale_coordinate(y, height, next_height),
+            scale_coordinate(z, depth, next_depth),
+        ))
+    }
+
+    /// Get the neighbors of a cell in the cube of timestep `ts`.
+    fn get_neighbors_at(&self, x: usize, y: usize, z: usize, ts: usize) -> Vec<usize> {
         let modifiers = [usize::MAX, 0, 1];
         let mut neighbors = Vec::new();
-        for i in modifiers.clone() {
-            for j in modifiers.clone() {
-                for k in modifiers.clone() {
+        for i in modifiers {
+            for j in modifiers {
+                for k in modifiers {
                     if i == 0 && j == 0 && k == 0 {
                         continue;
                     }
                     let n = self.cubes
-                        .get(ts + 1)
-                        .map(|xx| xx.get(x.wrapping_add(i)))
-                        .flatten()
-                        .map(|yy| yy.get(y.wrapping_add(j)))
-                        .flatten()
-                        .map(|zz| zz.get(z.wrapping_add(k)))
-                        .flatten()
+                        .get(ts)
+                        .and_then(|xx| xx.get(x.wrapping_add(i)))
+                        .and_then(|yy| yy.get(y.wrapping_add(j)))
+                        .and_then(|zz| zz.get(z.wrapping_add(k)))
                         .copied();
 
                     if let Some(n) = n {
@@ -135,10 +863,76 @@ impl CubeGraph {
         neighbors
     }
 
-    fn is_outer_vertex(&self, x: usize, y: usize, z: usize) -> bool {
-        x == 0 || x == self.width - 1 || 
-        y == 0 || y == self.height - 1 ||
-        z == 0 || z == self.depth - 1
+    fn is_outer_at(&self, x: usize, y: usize, z: usize, ts: usize) -> bool {
+        let (width, height, depth) = self.dims[ts];
+        x == 0 || x == width - 1 || 
+        y == 0 || y == height - 1 ||
+        z == 0 || z == depth - 1
+    }
+}
+
+/// Draws graphs with at most 4 timesteps of at most 6 cells in every dimension,
+/// so a fuzzer can't run out of memory. Timesteps can be empty.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CubeGraph {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        fn dims(u: &mut arbitrary::Unstructured) -> arbitrary::Result<(usize, usize, usize)> {
+            Ok((u.int_in_range(0..=6)?, u.int_in_range(0..=6)?, u.int_in_range(0..=6)?))
+        }
+        fn offset(u: &mut arbitrary::Unstructured) -> arbitrary::Result<(isize, isize, isize)> {
+            Ok((u.int_in_range(-2..=2)?, u.int_in_range(-2..=2)?, u.int_in_range(-2..=2)?))
+        }
+
+        let timesteps = u.int_in_range(0..=4)?;
+        let graph = if u.arbitrary()? {
+            let (width, height, depth) = dims(u)?;
+            CubeGraph::new(width, height, depth, timesteps)
+        } else {
+            let dims = (0..timesteps).map(|_| dims(u)).collect::<arbitrary::Result<_>>()?;
+            CubeGraph::new_variable(dims)
+        };
+
+        let neighborhood = *u.choose(&[Neighborhood::Temporal, Neighborhood::SpatialTemporal])?;
+        let coupling = if u.arbitrary()? {
+            CouplingPattern::Moore
+        } else {
+            let n_offsets = u.int_in_range(0..=4)?;
+            let offsets = (0..n_offsets).map(|_| offset(u)).collect::<arbitrary::Result<_>>()?;
+            CouplingPattern::Offsets(offsets)
+        };
+        let temporal_dir = u.int_in_range(0..=2)?;
+        let comm_id_base = match u.arbitrary()? {
+            true => Some(graph.cell_count() + u.int_in_range(0..=8)?),
+            false => None,
+        };
+
+        let graph = graph.with_neighborhood(neighborhood).with_coupling(coupling);
+        let graph = match temporal_dir {
+            0 => graph,
+            1 => graph.with_temporal_dir(|_| TemporalDir::Backward),
+            _ => graph.with_temporal_dir(|(x, y, z, ts)| match (x + y + z + ts) % 2 {
+                0 => TemporalDir::Forward,
+                _ => TemporalDir::Backward,
+            }),
+        };
+        Ok(match comm_id_base {
+            Some(base) => graph.with_comm_id_base(base),
+            None => graph,
+        })
+    }
+}
+
+/// Maps a coordinate in `0..size` to the coordinate in `0..next_size`
+/// with the nearest relative position. `next_size` must not be `0`.
+#[inline(always)]
+fn scale_coordinate(coordinate: usize, size: usize, next_size: usize) -> usize {
+    if size == next_size {
+        coordinate
+    } else if size == 1 {
+        (next_size - 1) / 2
+    } else {
+        // round half up
+        (2 * coordinate * (next_size - 1) + size - 1) / (2 * (size - 1))
     }
 }
 
@@ -150,9 +944,9 @@ fn cube_graph_vec() {
             for y in x {
                 println!("{y:?}");
             }
-            println!("");
+            println!();
         }
-        println!("");
+        println!();
     }
 }
 
@@ -166,8 +960,8 @@ fn cube_graph_neighbors() {
 #[test]
 fn cube_graph_is_outer() {
     let graph = CubeGraph::new(4, 4, 4, 1);
-    assert!(graph.is_outer_vertex(0, 1, 2));
-    assert!(!graph.is_outer_vertex(1, 1, 1));
+    assert!(graph.is_outer_at(0, 1, 2, 0));
+    assert!(!graph.is_outer_at(1, 1, 1, 0));
 }
 
 #[test]
@@ -177,10 +971,522 @@ fn cube_graph_build() {
 
 }
 
+#[test]
+fn cube_graph_try_new() {
+    let graph = CubeGraph::try_new(3, 4, 2, 3).unwrap();
+    assert_eq!(graph.build(), CubeGraph::new(3, 4, 2, 3).build());
+    assert!(matches!(
+        CubeGraph::try_new(3, 0, 2, 3),
+        Err(GraphError::InvalidDimension { name: "height", value: 0 })
+    ));
+    assert!(matches!(
+        CubeGraph::try_new(3, 3, 3, 0),
+        Err(GraphError::InvalidDimension { name: "timesteps", .. })
+    ));
+    assert!(matches!(
+        CubeGraph::try_new(usize::MAX / 2, 2, 2, 3),
+        Err(GraphError::Overflow)
+    ));
+}
+
+#[test]
+fn cube_graph_validate() {
+    let graph = CubeGraph::new(40, 40, 40, 4);
+    assert!(graph.validate().is_ok());
+    assert_eq!(graph.node_count(), 40 * 40 * 40 * 4 + 3);
+
+    let with_dims = |dims| CubeGraph { dims, ..CubeGraph::new(1, 1, 1, 2) };
+    let overflowing = with_dims(vec![(usize::MAX, 2, 1), (1, 1, 1)]);
+    assert!(matches!(overflowing.validate(), Err(GraphError::Overflow)));
+    let overflowing = with_dims(vec![(usize::MAX, 1, 1), (1, 1, 1)]);
+    assert!(matches!(overflowing.validate(), Err(GraphError::Overflow)));
+    let largest = CubeGraph { dims: vec![(usize::MAX, 1, 1)], ..CubeGraph::new(1, 1, 1, 1) };
+    assert!(largest.validate().is_ok());
+    // the cells fit, but the comm vertices don't
+    let overflowing = CubeGraph {
+        dims: vec![(usize::MAX / 3, 1, 1); 3],
+        ..CubeGraph::new(1, 1, 1, 3)
+    };
+    assert!(matches!(overflowing.validate(), Err(GraphError::Overflow)));
+}
+
+#[test]
+fn cube_graph_new_variable() {
+    use std::collections::HashSet;
+    let graph = CubeGraph::new_variable(vec![(3, 3, 3), (5, 5, 5)]);
+    let edges = graph.build();
+    let comm = 27 + 125;
+    assert_eq!(graph.node_count(), comm + 1);
+
+    let next_id = |x: usize, y: usize, z: usize| 27 + (x * 5 + y) * 5 + z;
+    let linked = edges
+        .iter()
+        .filter(|(t, _)| *t == comm)
+        .map(|(_, h)| *h)
+        .collect::<HashSet<_>>();
+    // corners map to corners, edge centers to edge centers
+    assert!(linked.contains(&next_id(0, 0, 0)));
+    assert!(linked.contains(&next_id(4, 4, 4)));
+    assert!(linked.contains(&next_id(2, 0, 0)));
+    assert!(!linked.contains(&next_id(1, 0, 0)));
+    assert_eq!(linked.len(), 26);
+
+    // every neighbor is around the counterpart of the cell
+    for (tail, head) in edges.iter().filter(|(t, h)| *t < 27 && *h != comm) {
+        let (x, y, z) = (tail / 9, tail / 3 % 3, tail % 3);
+        let (hx, hy, hz) = ((head - 27) / 25, (head - 27) / 5 % 5, (head - 27) % 5);
+        assert!(hx.abs_diff(2 * x) <= 1 && hy.abs_diff(2 * y) <= 1 && hz.abs_diff(2 * z) <= 1);
+    }
+
+    // uniform dimensions behave exactly like new
+    assert_eq!(
+        CubeGraph::new_variable(vec![(3, 4, 5); 3]).build(),
+        CubeGraph::new(3, 4, 5, 3).build()
+    );
+}
+
+#[test]
+fn cube_graph_new_variable_empty_next_timestep() {
+    // the cells are not linked to an empty timestep, so the comm vertex is isolated
+    let graph = CubeGraph::new_variable(vec![(3, 3, 3), (0, 0, 0)]);
+    assert!(graph.build().is_empty());
+    assert_eq!(graph.edge_count(), 0);
+    assert_eq!(graph.comm_vertex_for(0, 0, 0, 0), None);
+    assert_eq!(graph.node_count(), 28);
+
+    let graph = graph.with_neighborhood(Neighborhood::SpatialTemporal);
+    assert_eq!(graph.build().len(), graph.edge_count());
+    assert!(graph.build().iter().all(|(tail, head)| *tail < 27 && *head < 27));
+
+    let graph = CubeGraph::new_variable(vec![(1, 1, 1), (0, 0, 0)]);
+    assert!(graph.build().is_empty());
+    assert_eq!(graph.edge_count(), 0);
+    assert_eq!(graph.comm_vertex_for(0, 0, 0, 0), None);
+    let graph = graph.with_coupling(CouplingPattern::Offsets(vec![(0, 0, 0)]));
+    assert!(graph.build().is_empty());
+    assert_eq!(graph.edge_count(), 0);
+}
+
+#[test]
+fn cube_graph_comm_id_base() {
+    let base = 1000;
+    let graph = CubeGraph::new(3, 3, 3, 4).with_comm_id_base(base);
+    assert!(graph.validate().is_ok());
+    let edges = graph.build();
+
+    let plain = CubeGraph::new(3, 3, 3, 4).build();
+    assert_eq!(edges.len(), plain.len());
+    let ids = edges.iter().flat_map(|(t, h)| [*t, *h]).collect::<Vec<_>>();
+    let comm_ids = ids.iter().filter(|id| **id >= graph.cell_count()).collect::<Vec<_>>();
+    assert!(!comm_ids.is_empty());
+    assert!(comm_ids.iter().all(|id| (base..base + 3).contains(*id)));
+    assert_eq!(graph.comm_vertex_for(0, 0, 0, 2), Some(base + 2));
+    assert_eq!(graph.timestep_partition()[1].last(), Some(&(base as u32 + 1)));
+
+    let overflowing = CubeGraph::new(3, 3, 3, 4).with_comm_id_base(usize::MAX - 1);
+    assert!(matches!(overflowing.validate(), Err(GraphError::Overflow)));
+}
+
+#[test]
+#[should_panic]
+fn cube_graph_comm_id_base_collision() {
+    CubeGraph::new(3, 3, 3, 2).with_comm_id_base(53);
+}
+
+#[test]
+fn cube_graph_build_with_stats() {
+    use crate::graph::Graph;
+    let graphs = [
+        CubeGraph::new(3, 3, 3, 3),
+        CubeGraph::new(4, 2, 5, 2).with_neighborhood(Neighborhood::SpatialTemporal),
+        CubeGraph::new(3, 3, 3, 0),
+    ];
+    for graph in graphs {
+        let (edges, stats) = graph.build_with_stats();
+        assert_eq!(edges, graph.build());
+        let expected = Graph::new(
+            edges.iter().map(|(t, h)| (*t as u32, *h as u32)).collect(),
+            graph.node_count(),
+        )
+        .stats();
+        assert_eq!(stats, expected);
+    }
+
+    let (_, stats) = CubeGraph::new(3, 3, 3, 3).with_comm_id_base(100).build_with_stats();
+    assert_eq!(stats, CubeGraph::new(3, 3, 3, 3).build_with_stats().1);
+}
+
+#[test]
+fn cube_graph_build_weighted_split() {
+    let graph = CubeGraph::new(3, 3, 3, 3).with_neighborhood(Neighborhood::SpatialTemporal);
+    let edges = graph.build_weighted_split(1., 25.);
+    let first_comm_id = graph.cell_count();
+    for (tail, head, weight) in &edges {
+        if *tail >= first_comm_id || *head >= first_comm_id {
+            assert_eq!(*weight, 25.);
+        } else {
+            assert_eq!(*weight, 1.);
+        }
+    }
+    let comm_edges = edges.iter().filter(|(_, _, w)| *w == 25.).count();
+    assert_eq!(comm_edges, graph.comm_edge_count());
+}
+
+#[test]
+fn cube_graph_build_adjacency() {
+    let graph = CubeGraph::new(3, 3, 2, 3).with_neighborhood(Neighborhood::SpatialTemporal);
+    let (out_adj, in_adj) = graph.build_adjacency();
+    assert_eq!(out_adj.len(), graph.node_count());
+    for (u, successors) in out_adj.iter().enumerate() {
+        for v in successors {
+            assert!(in_adj[*v as usize].contains(&(u as u32)));
+        }
+    }
+    for (v, predecessors) in in_adj.iter().enumerate() {
+        for u in predecessors {
+            assert!(out_adj[*u as usize].contains(&(v as u32)));
+        }
+    }
+    let n_edges = out_adj.iter().map(Vec::len).sum::<usize>();
+    assert_eq!(n_edges, graph.edge_count());
+    assert_eq!(in_adj.iter().map(Vec::len).sum::<usize>(), n_edges);
+
+    let (out_adj, _) = CubeGraph::new(3, 3, 2, 3).with_comm_id_base(100).build_adjacency();
+    assert_eq!(out_adj.len(), 102);
+    // with a depth of 2, every cell is an outer cell
+    assert_eq!(out_adj[101].len(), 3 * 3 * 2);
+}
+
+#[test]
+fn cube_graph_build_undirected() {
+    use std::collections::HashSet;
+    let graph = CubeGraph::new(3, 4, 2, 3);
+    let pairs = graph.build_undirected();
+    assert!(pairs.iter().all(|(a, b)| a < b));
+    assert_eq!(pairs.iter().collect::<HashSet<_>>().len(), pairs.len());
+
+    // symmetric, so it contains every directed edge in either direction
+    let spatial_temporal = CubeGraph::new(3, 4, 2, 3)
+        .with_neighborhood(Neighborhood::SpatialTemporal)
+        .build();
+    assert_eq!(pairs.len(), spatial_temporal.len());
+    for (tail, head) in spatial_temporal {
+        assert!(pairs.contains(&(tail, head)) || pairs.contains(&(head, tail)));
+    }
+    assert_eq!(
+        graph.with_neighborhood(Neighborhood::SpatialTemporal).build_undirected(),
+        pairs
+    );
+}
+
+#[test]
+fn cube_graph_dimensions() {
+    let graph = CubeGraph::new(4, 2, 5, 3);
+    assert_eq!(
+        (graph.width(), graph.height(), graph.depth(), graph.timesteps()),
+        (4, 2, 5, 3)
+    );
+
+    let graph = CubeGraph::new_variable(vec![(3, 3, 3), (5, 5, 5)]);
+    assert_eq!((graph.width(), graph.timesteps()), (3, 2));
+}
+
+#[test]
+fn cube_graph_edge_counts() {
+    let graphs = [
+        CubeGraph::new(3, 3, 3, 2),
+        CubeGraph::new(4, 2, 5, 3),
+        CubeGraph::new(1, 1, 1, 3),
+        CubeGraph::new(3, 3, 3, 1),
+        CubeGraph::new(4, 4, 4, 3).with_neighborhood(Neighborhood::SpatialTemporal),
+        CubeGraph::new_variable(vec![(3, 3, 3), (5, 5, 5), (2, 4, 1)]),
+        CubeGraph::new_variable(vec![(3, 3, 3), (5, 5, 5), (2, 4, 1)])
+            .with_neighborhood(Neighborhood::SpatialTemporal),
+        CubeGraph::new_variable(vec![(3, 3, 3), (5, 5, 5), (2, 4, 1)])
+            .with_coupling(CouplingPattern::Offsets(vec![(1, 1, 1), (0, -1, 0)])),
+    ];
+    for graph in graphs {
+        let edges = graph.build();
+        let first_comm_id = graph.cell_count();
+        let comm_edges = edges
+            .iter()
+            .filter(|(t, h)| *t >= first_comm_id || *h >= first_comm_id)
+            .count();
+        assert_eq!(graph.comm_edge_count(), comm_edges);
+        assert_eq!(graph.neighbor_edge_count(), edges.len() - comm_edges);
+        assert_eq!(graph.edge_count(), edges.len());
+    }
+}
+
+#[test]
+fn cube_graph_coupling_pattern() {
+    let graph = CubeGraph::new(4, 4, 4, 2)
+        .with_coupling(CouplingPattern::Offsets(vec![(1, 1, 1)]));
+    let edges = graph.build();
+    for x in 1..3 {
+        for y in 1..3 {
+            for z in 1..3 {
+                let cur = graph.cubes[0][x][y][z];
+                let temporal = edges
+                    .iter()
+                    .filter(|(t, h)| *t == cur && *h < graph.cell_count())
+                    .map(|(_, h)| *h)
+                    .collect::<Vec<_>>();
+                assert_eq!(temporal, vec![graph.cubes[1][x + 1][y + 1][z + 1]]);
+            }
+        }
+    }
+}
+
+#[test]
+fn cube_graph_boundary_class() {
+    let graph = CubeGraph::new(3, 3, 3, 2);
+    assert_eq!(graph.boundary_class(0, 0, 0), Some(BoundaryClass::Corner));
+    assert_eq!(graph.boundary_class(2, 0, 1), Some(BoundaryClass::Edge));
+    assert_eq!(graph.boundary_class(0, 1, 1), Some(BoundaryClass::Face));
+    assert_eq!(graph.boundary_class(1, 1, 1), Some(BoundaryClass::Interior));
+    assert_eq!(graph.boundary_class(7, 7, 7), None);
+    assert_eq!(graph.boundary_class(1, 3, 1), None);
+    assert_eq!(CubeGraph::new(3, 3, 3, 0).boundary_class(0, 0, 0), None);
+    assert_eq!(CubeGraph::new(1, 1, 1, 1).boundary_class(0, 0, 0), Some(BoundaryClass::Corner));
+
+    // the classification refines is_outer_at
+    for (x, y, z) in (0..27).map(|i| (i / 9, i / 3 % 3, i % 3)) {
+        let is_interior = graph.boundary_class(x, y, z) == Some(BoundaryClass::Interior);
+        assert_eq!(graph.is_outer_at(x, y, z, 0), !is_interior);
+    }
+}
+
+#[test]
+fn cube_graph_comm_vertex_for() {
+    let graph = CubeGraph::new(3, 3, 3, 3);
+    let edges = graph.build();
+    for ts in 0..2 {
+        let comm = graph.comm_vertex_for(0, 1, 2, ts).unwrap();
+        let cell = graph.cubes[ts][0][1][2];
+        assert!(edges.contains(&(cell, comm)));
+    }
+    // inner cell, last timestep and cells outside of the cube
+    assert_eq!(graph.comm_vertex_for(1, 1, 1, 0), None);
+    assert_eq!(graph.comm_vertex_for(0, 0, 0, 2), None);
+    assert_eq!(graph.comm_vertex_for(3, 0, 0, 0), None);
+    assert_eq!(graph.comm_vertex_for(0, 0, 0, 5), None);
+}
+
+#[test]
+fn cube_graph_timestep_partition() {
+    let graph = CubeGraph::new_variable(vec![(2, 2, 2), (3, 3, 3), (2, 3, 4)]);
+    let partition = graph.timestep_partition();
+    assert_eq!(partition.len(), 3);
+    assert_eq!(partition.iter().map(Vec::len).sum::<usize>(), graph.node_count());
+
+    let cells = |group: &Vec<u32>| {
+        group
+            .iter()
+            .filter(|id| (**id as usize) < graph.cell_count())
+            .count()
+    };
+    assert_eq!(cells(&partition[0]), 8);
+    assert_eq!(cells(&partition[1]), 27);
+    assert_eq!(cells(&partition[2]), 24);
+    assert_eq!(partition[0][0], 0);
+    assert_eq!(partition[1][0], 8);
+    // the comm vertex between timestep 0 and 1 is grouped with timestep 0
+    assert_eq!(partition[0].last(), Some(&(graph.cell_count() as u32)));
+}
+
+#[test]
+fn cube_graph_build_filtered() {
+    let graph = CubeGraph::new(4, 4, 4, 3).with_neighborhood(Neighborhood::SpatialTemporal);
+    let edges = graph.build_filtered(|tail, head| tail.0 != 0 && head.0 != 0);
+    assert!(!edges.is_empty());
+
+    let coordinates = graph.coordinates();
+    let touches_x0 = |id: usize| id < graph.cell_count() && coordinates[id].0 == 0;
+    assert!(edges.iter().all(|(t, h)| !touches_x0(*t) && !touches_x0(*h)));
+
+    let expected = graph
+        .build()
+        .into_iter()
+        .filter(|(t, h)| !touches_x0(*t) && !touches_x0(*h))
+        .collect::<Vec<_>>();
+    assert_eq!(edges, expected);
+    assert_eq!(graph.build_filtered(|_, _| true), graph.build());
+}
+
+#[test]
+fn cube_graph_build_shuffled() {
+    let graph = CubeGraph::new(3, 3, 3, 3);
+    let shuffled = graph.build_shuffled(11);
+    assert_ne!(shuffled, graph.build());
+    assert_eq!(shuffled, graph.build_shuffled(11));
+
+    let mut sorted = shuffled;
+    sorted.sort_unstable();
+    let mut expected = graph.build();
+    expected.sort_unstable();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn cube_graph_build_weighted_with() {
+    let graph = CubeGraph::new(3, 3, 3, 3);
+    let edges = graph.build_weighted_with(|_, _| 2.5);
+    assert_eq!(edges.len(), graph.build().len());
+    assert!(edges.iter().all(|(_, _, w)| *w == 2.5));
+
+    // the coordinates passed to the weight function belong to the ids of the edge
+    let coordinates = graph.coordinates();
+    assert_eq!(coordinates.len(), graph.cell_count());
+    assert_eq!(coordinates[graph.cubes[1][2][0][1]], (2, 0, 1, 1));
+    let edges = graph.build_weighted_with(|tail, _| match tail {
+        (usize::MAX, usize::MAX, usize::MAX, ts) => 10. + ts as f64,
+        _ => 0.,
+    });
+    for (tail, _, weight) in edges {
+        match tail.checked_sub(graph.cell_count()) {
+            Some(ts) => assert_eq!(weight, 10. + ts as f64),
+            None => assert_eq!(weight, 0.),
+        }
+    }
+    let edges = graph.build_weighted_with(|tail, head| (head.3 - tail.3) as f64);
+    assert!(edges.iter().all(|(_, _, w)| *w == 0. || *w == 1.));
+}
+
+#[test]
+fn cube_graph_write_sectioned() {
+    let filename = std::env::temp_dir().join("graph_generator_sectioned.txt");
+    let filename = filename.to_str().unwrap();
+    let graph = CubeGraph::new(3, 3, 3, 4).with_neighborhood(Neighborhood::SpatialTemporal);
+    graph.write_sectioned(filename).unwrap();
+
+    let content = std::fs::read_to_string(filename).unwrap();
+    let headers = content.lines().filter(|l| l.starts_with("# timestep")).count();
+    assert_eq!(headers, 3);
+
+    let mut edges = crate::read_from_file(filename).unwrap();
+    let mut expected = graph
+        .build()
+        .into_iter()
+        .map(|(t, h)| (t as u32, h as u32))
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(edges, expected);
+}
+
+#[test]
+fn cube_graph_write_coords() {
+    let filename = std::env::temp_dir().join("graph_generator_cube.coords");
+    let filename = filename.to_str().unwrap();
+    let graph = CubeGraph::new_variable(vec![(3, 2, 4), (1, 2, 2), (2, 2, 2)]);
+    graph.write_coords(filename).unwrap();
+
+    let content = std::fs::read_to_string(filename).unwrap();
+    let (cells, comms): (Vec<_>, Vec<_>) = content.lines().partition(|l| !l.contains('-'));
+    assert_eq!(cells.len(), graph.cell_count());
+    assert_eq!(comms.len(), 2);
+    for line in cells {
+        let fields = line
+            .split(' ')
+            .map(|field| field.parse().unwrap())
+            .collect::<Vec<usize>>();
+        let id = fields[0];
+        assert_eq!(graph.coords(id), Some((fields[1], fields[2], fields[3], fields[4])));
+        let (x, y, z, ts) = graph.coords(id).unwrap();
+        assert_eq!(graph.cubes[ts][x][y][z], id);
+    }
+    assert_eq!(comms[0], format!("{} - - - 0", graph.cell_count()));
+    assert_eq!(graph.coords(graph.cell_count()), None);
+}
+
+#[test]
+fn cube_graph_window_edges() {
+    use std::collections::HashSet;
+    let graph = CubeGraph::new(3, 3, 3, 5).with_neighborhood(Neighborhood::SpatialTemporal);
+    let all = graph.build().into_iter().collect::<HashSet<_>>();
+    let window = graph.window_edges(1, 3);
+    assert!(!window.is_empty());
+
+    let cells = 27;
+    let first_comm = cells * 5;
+    let timestep = |id: usize| if id < first_comm { id / cells } else { id - first_comm };
+    for edge in &window {
+        assert!(all.contains(edge));
+        assert!((1..3).contains(&timestep(edge.0)));
+    }
+    // comm edges into the last timestep of the window are included
+    assert!(window.iter().any(|(t, h)| *t == first_comm + 2 && (3 * cells..4 * cells).contains(h)));
+    assert_eq!(graph.window_edges(0, 5), graph.build());
+}
+
+#[test]
+fn cube_graph_window_edges_backward() {
+    let graph = CubeGraph::new(3, 3, 3, 4).with_temporal_dir(|_| TemporalDir::Backward);
+    let (cells, first_comm) = (27, 27 * 4);
+    let window = graph.window_edges(1, 2);
+    // the link through the comm vertex of the window starts in the next timestep
+    assert!(window.contains(&(2 * cells, first_comm + 1)));
+    assert!(window.contains(&(first_comm + 1, cells)));
+    assert!(!window.iter().any(|(t, h)| *t == first_comm + 2 || *h == first_comm + 2));
+
+    // windows of consecutive timesteps split the edges
+    let mut windows = (0..4).flat_map(|ts| graph.window_edges(ts, ts + 1)).collect::<Vec<_>>();
+    let mut all = graph.build();
+    windows.sort_unstable();
+    all.sort_unstable();
+    assert_eq!(windows, all);
+}
+
+#[test]
+fn cube_graph_temporal_dir() {
+    let forward = CubeGraph::new(3, 3, 3, 2).build();
+    assert!(forward.contains(&(0, 54)) && forward.contains(&(54, 27)));
+
+    let backward = CubeGraph::new(3, 3, 3, 2)
+        .with_temporal_dir(|(x, _, _, _)| match x {
+            0 => TemporalDir::Backward,
+            _ => TemporalDir::Forward,
+        })
+        .build();
+    assert_eq!(backward.len(), forward.len());
+    assert!(backward.contains(&(27, 54)) && backward.contains(&(54, 0)));
+    assert!(!backward.contains(&(0, 54)));
+    // cells with x != 0 keep their direction
+    assert!(backward.contains(&(26, 54)) && backward.contains(&(54, 53)));
+}
+
+#[test]
+fn cube_graph_build_spatial_temporal() {
+    let cells = 27;
+    let interior = 13;
+    let temporal = CubeGraph::new(3, 3, 3, 2).build();
+    assert!(!temporal.iter().any(|(t, h)| *t == interior && *h < cells));
+
+    let edges = CubeGraph::new(3, 3, 3, 2)
+        .with_neighborhood(Neighborhood::SpatialTemporal)
+        .build();
+    let successors = edges
+        .iter()
+        .filter(|(t, _)| *t == interior)
+        .map(|(_, h)| *h)
+        .collect::<Vec<_>>();
+
+    // same timestep
+    assert!(successors.iter().any(|h| *h < cells));
+    // next timestep
+    assert!(successors.iter().any(|h| (cells..2 * cells).contains(h)));
+    assert!(crate::util::topological_sort(
+        &edges.iter().map(|(t, h)| (*t as u32, *h as u32)).collect::<Vec<_>>(),
+        2 * cells + 1
+    )
+    .is_some());
+}
+
 /// Builds a cube graph:
 /// consists of a series of cubes, with a cube being some state at a given timestep
 ///
-///```
+///```text
 ///     2  5  8     t = 0
 ///   1  4  7
 /// 0  3  6  17
@@ -256,11 +1562,12 @@ impl CubeGraphOld {
             }
         }
 
+        // the set is ordered, so the output is sorted
         edges
             .into_iter()
-            .collect::<HashSet<_>>()
+            .collect::<BTreeSet<_>>()
             .into_iter()
-            .collect()
+            .collect::<Vec<_>>()
     }
 
     fn is_outer_vertex(&self, id: isize) -> bool {
@@ -322,7 +1629,7 @@ impl CubeGraphOld {
     #[inline(always)]
     fn calc_neighbor(&self, id: isize, i: usize, j: usize, k: usize) -> isize {
         let ops = [0, 1, -1];
-        id + self.depth * self.width * ops[i] + 1 * ops[j] + self.depth * ops[k]
+        id + self.depth * self.width * ops[i] + ops[j] + self.depth * ops[k]
     }
 }
 
@@ -395,6 +1702,91 @@ fn test_create_comp_graph() {
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn test_create_comp_graph_neighbors_respect_layers() {
+    let (inside, outside, n_layers) = (4, 2, 5);
+    let nodes_per_layer = inside + outside;
+    let n_cells = nodes_per_layer * n_layers;
+
+    let neighbor_edges = comp_graph(inside, outside, n_layers)
+        .into_iter()
+        .filter(|(t, h)| *t < n_cells && *h < n_cells)
+        .collect::<Vec<_>>();
+    assert_eq!(neighbor_edges.len(), (3 * nodes_per_layer - 2) * (n_layers - 1));
+
+    for (tail, head) in neighbor_edges {
+        assert_eq!(head / nodes_per_layer, tail / nodes_per_layer + 1);
+        let column_offset = (head % nodes_per_layer) as isize - (tail % nodes_per_layer) as isize;
+        assert!((-1..=1).contains(&column_offset));
+    }
+}
+
+#[test]
+fn test_comp_graph_topology() {
+    let (inside, outside, n_layers) = (3, 2, 4);
+    let first_comm = (inside + outside) * n_layers;
+
+    let broadcast = CompGraph::new(inside, outside, n_layers)
+        .with_topology(CommTopology::Broadcast)
+        .build();
+    assert_eq!(broadcast, comp_graph(inside, outside, n_layers));
+    for comm in first_comm..first_comm + n_layers - 1 {
+        assert_eq!(broadcast.iter().filter(|(_, h)| *h == comm).count(), outside);
+        assert_eq!(broadcast.iter().filter(|(t, _)| *t == comm).count(), outside);
+    }
+
+    let matched = CompGraph::new(inside, outside, n_layers)
+        .with_topology(CommTopology::Matched)
+        .build();
+    let comm_vertices = (n_layers - 1) * outside;
+    for comm in first_comm..first_comm + comm_vertices {
+        let incoming = matched.iter().filter(|(_, h)| *h == comm).collect::<Vec<_>>();
+        let outgoing = matched.iter().filter(|(t, _)| *t == comm).collect::<Vec<_>>();
+        assert_eq!((incoming.len(), outgoing.len()), (1, 1));
+        // connects the same column
+        assert_eq!(incoming[0].0 + inside + outside, outgoing[0].1);
+    }
+}
+
+#[test]
+fn test_comp_graph_neighbor_mode() {
+    let (inside, outside, n_layers) = (3, 2, 4);
+    let first_comm = (inside + outside) * n_layers;
+    let is_comm_edge = |(tail, head): &(usize, usize)| *tail >= first_comm || *head >= first_comm;
+    let graph = |mode| {
+        CompGraph::new(inside, outside, n_layers)
+            .with_neighbor_mode(mode)
+            .build()
+    };
+
+    let full = graph(NeighborMode::Full);
+    assert_eq!(full, comp_graph(inside, outside, n_layers));
+    let comm_only = graph(NeighborMode::CommOnly);
+    assert!(comm_only.iter().all(is_comm_edge));
+    assert_eq!(comm_only.len(), 2 * outside * (n_layers - 1));
+    let neighbor_only = graph(NeighborMode::NeighborOnly);
+    assert!(!neighbor_only.iter().any(is_comm_edge));
+    assert_eq!(neighbor_only.len() + comm_only.len(), full.len());
+}
+
+#[test]
+fn test_comp_graph_layer_of() {
+    let graph = CompGraph::new(3, 2, 4);
+    assert_eq!(graph.layer_of(0), Some(0));
+    assert_eq!(graph.layer_of(4), Some(0));
+    assert_eq!(graph.layer_of(5), Some(1));
+    assert_eq!(graph.layer_of(19), Some(3));
+    // comm vertices
+    assert_eq!(graph.layer_of(20), None);
+
+    // edges between vertices go to the next layer
+    for (tail, head) in graph.build() {
+        if let (Some(upper), Some(lower)) = (graph.layer_of(tail), graph.layer_of(head)) {
+            assert_eq!(upper + 1, lower);
+        }
+    }
+}
+
 #[test]
 fn test_create_comp_graph_larg() {
     let edges = comp_graph(10, 5, 10)
@@ -404,15 +1796,73 @@ fn test_create_comp_graph_larg() {
     println!("{:?}", edges);
 }
 
+#[test]
 fn test_cube_graph() {
     let mut edges = CubeGraphOld::new(3, 3, 3, 2).build();
-    edges.sort_by(|a, b| a.0.cmp(&b.0));
+    edges.sort_by_key(|e| e.0);
     println!("{edges:?}\n{}", edges.len());
 }
 
+#[test]
+fn test_cube_graph_old_deterministic() {
+    let graph = CubeGraphOld::new(3, 3, 3, 3);
+    let edges = graph.build();
+    assert_eq!(edges, graph.build());
+    assert!(edges.windows(2).all(|w| w[0] < w[1]));
+}
+
 #[test]
 fn test_neighbor_indices() {
     let g = CubeGraphOld::new(3, 3, 3, 3);
     let neighbors = g.get_neighbor_indices(13);
     println!("{neighbors:?}");
 }
+
+#[test]
+fn cube_graph_degenerate_dimensions() {
+    let dims = [(3, 3, 3, 0), (0, 0, 0, 0), (1, 1, 1, 0), (1, 1, 1, 1), (0, 3, 3, 2), (3, 0, 2, 3)];
+    for (width, height, depth, timesteps) in dims {
+        let graph = CubeGraph::new(width, height, depth, timesteps);
+        let edges = graph.build();
+        assert_eq!(edges.len(), graph.edge_count());
+        assert_eq!(graph.timestep_partition().len(), timesteps);
+        assert_eq!(graph.window_edges(0, timesteps + 2), edges);
+        assert_eq!(graph.build_undirected().len(), edges.len());
+    }
+
+    // empty timesteps anywhere between the others
+    let dims_per_timestep = [
+        vec![(3, 3, 3), (0, 0, 0)],
+        vec![(1, 1, 1), (0, 0, 0)],
+        vec![(0, 0, 0), (2, 2, 2)],
+        vec![(2, 3, 1), (3, 0, 3), (1, 1, 1)],
+        vec![(0, 0, 0), (0, 0, 0), (0, 0, 0)],
+    ];
+    for dims in dims_per_timestep {
+        let timesteps = dims.len();
+        let graph = CubeGraph::new_variable(dims).with_neighborhood(Neighborhood::SpatialTemporal);
+        let edges = graph.build();
+        assert_eq!(edges.len(), graph.edge_count());
+        assert_eq!(graph.timestep_partition().len(), timesteps);
+        assert_eq!(graph.window_edges(0, timesteps + 2), edges);
+        let (out_adj, _) = graph.build_adjacency();
+        assert_eq!(out_adj.iter().map(Vec::len).sum::<usize>(), edges.len());
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn cube_graph_arbitrary() {
+    let mut rng = Lcg::with_seed(7);
+    let mut data = [0; 64];
+    for _ in 0..500 {
+        rng.fill_bytes(&mut data);
+        let mut u = arbitrary::Unstructured::new(&data);
+        let graph = <CubeGraph as arbitrary::Arbitrary>::arbitrary(&mut u).unwrap();
+        assert!(graph.timesteps() <= 4);
+        assert!(graph.dims.iter().all(|(w, h, d)| *w <= 6 && *h <= 6 && *d <= 6));
+        assert_eq!(graph.build().len(), graph.edge_count());
+        graph.build_undirected();
+        graph.build_adjacency();
+    }
+}
@@ -0,0 +1,64 @@
+use alloc::string::String;
+use core::fmt;
+
+/// Errors which can occur while creating a graph, or while writing or reading it.
+#[derive(Debug)]
+pub enum GraphError {
+    /// The edges contain a cycle, but the graph is required to be acyclic.
+    ContainsCycle,
+    /// The number of nodes does not fit into the id type.
+    Overflow,
+    /// A size parameter of a generator, like a dimension or the degree, has an invalid value.
+    InvalidDimension { name: &'static str, value: usize },
+    /// The generator can not create a graph with this number of layers.
+    TooFewLayers { layers: usize },
+    /// More edges were requested than the graph can have.
+    InfeasibleEdgeCount { requested: usize, max: usize },
+    /// A [`GraphSpec`](crate::graph::GraphSpec) could not be parsed, for the given reason.
+    InvalidSpec(String),
+    /// Reading or writing a file failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::ContainsCycle => write!(f, "the edges contain a cycle"),
+            GraphError::Overflow => write!(f, "the number of nodes overflows the id type"),
+            GraphError::InvalidDimension { name, value } => {
+                write!(f, "invalid {} {}", name, value)
+            }
+            GraphError::TooFewLayers { layers } => {
+                write!(f, "a graph can not be created with {} layers", layers)
+            }
+            GraphError::InfeasibleEdgeCount { requested, max } => {
+                write!(
+                    f,
+                    "{} edges were requested, but at most {} are possible",
+                    requested, max
+                )
+            }
+            GraphError::InvalidSpec(reason) => write!(f, "invalid graph spec: {}", reason),
+            #[cfg(feature = "std")]
+            GraphError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GraphError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for GraphError {
+    fn from(err: std::io::Error) -> Self {
+        GraphError::Io(err)
+    }
+}
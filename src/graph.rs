@@ -0,0 +1,529 @@
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::str::FromStr;
+
+use super::comm::{CompGraph, CubeGraph};
+use super::error::GraphError;
+use super::layered::LayeredGraph;
+use super::random::RandomGraph;
+use super::topology::hypercube;
+use super::util::{disjoint_union, in_degrees, is_acyclic, out_degrees};
+
+/// A graph given by its edges and the number of its nodes.
+/// Nodes are numbered from `0` to `n_nodes - 1`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Graph {
+    pub n_nodes: usize,
+    pub edges: Vec<(u32, u32)>,
+}
+
+impl Graph {
+    pub fn new(edges: Vec<(u32, u32)>, n_nodes: usize) -> Self {
+        Self { n_nodes, edges }
+    }
+
+    /// Create a graph from its edges, the number of nodes is one more than the highest id.
+    pub fn from_edges(edges: Vec<(u32, u32)>) -> Self {
+        let n_nodes = edges
+            .iter()
+            .map(|(tail, head)| *tail.max(head) as usize + 1)
+            .max()
+            .unwrap_or(0);
+        Self { n_nodes, edges }
+    }
+
+    /// Add a new node without any edges, returning its id.
+    pub fn add_node(&mut self) -> u32 {
+        self.n_nodes += 1;
+        self.n_nodes as u32 - 1
+    }
+
+    /// Add an edge, growing the number of nodes if one of its ends is not part of the graph yet.
+    pub fn add_edge(&mut self, tail: u32, head: u32) {
+        self.n_nodes = self.n_nodes.max(tail.max(head) as usize + 1);
+        self.edges.push((tail, head));
+    }
+
+    /// The ids of all nodes in the graph, including the ones without edges.
+    pub fn node_ids(&self) -> Range<u32> {
+        0..self.n_nodes as u32
+    }
+
+    /// Checks if the graph is a directed acyclic graph.
+    pub fn is_dag(&self) -> bool {
+        is_acyclic(&self.edges, self.n_nodes)
+    }
+
+    /// Calculates the number of nodes and edges and the extremes of the degrees.
+    pub fn stats(&self) -> GraphStats {
+        GraphStats::from_degrees(
+            self.edges.len(),
+            &out_degrees(&self.edges, self.n_nodes),
+            &in_degrees(&self.edges, self.n_nodes),
+        )
+    }
+}
+
+/// Summary of the size and the degrees of a graph.
+/// The degrees are all `0` for a graph without nodes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GraphStats {
+    pub n_nodes: usize,
+    pub n_edges: usize,
+    pub min_out_degree: usize,
+    pub max_out_degree: usize,
+    pub min_in_degree: usize,
+    pub max_in_degree: usize,
+}
+
+impl GraphStats {
+    /// Summarize the degrees of every node, which are indexed the same way.
+    pub(crate) fn from_degrees(
+        n_edges: usize,
+        out_degrees: &[usize],
+        in_degrees: &[usize],
+    ) -> Self {
+        let min = |degrees: &[usize]| degrees.iter().copied().min().unwrap_or(0);
+        let max = |degrees: &[usize]| degrees.iter().copied().max().unwrap_or(0);
+        Self {
+            n_nodes: out_degrees.len(),
+            n_edges,
+            min_out_degree: min(out_degrees),
+            max_out_degree: max(out_degrees),
+            min_in_degree: min(in_degrees),
+            max_in_degree: max(in_degrees),
+        }
+    }
+}
+
+impl From<Vec<(u32, u32)>> for Graph {
+    fn from(edges: Vec<(u32, u32)>) -> Self {
+        Graph::from_edges(edges)
+    }
+}
+
+impl From<Vec<(usize, usize)>> for Graph {
+    fn from(edges: Vec<(usize, usize)>) -> Self {
+        Graph::from_edges(
+            edges
+                .into_iter()
+                .map(|(tail, head)| (tail as u32, head as u32))
+                .collect(),
+        )
+    }
+}
+
+/// Describes a graph to create with [`generate`], with the parameters of its generator.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphSpec {
+    /// A [`CubeGraph`] with the same dimensions in every timestep.
+    Cube {
+        width: usize,
+        height: usize,
+        depth: usize,
+        timesteps: usize,
+    },
+    /// A [`RandomGraph`] created with [`RandomGraph::with_num_nodes`].
+    Random {
+        num_nodes: u32,
+        num_edges: u32,
+        seed: u64,
+    },
+    /// A [`LayeredGraph`] created with [`LayeredGraph::new_from_num_nodes`].
+    Layered { num_nodes: u32, edges_per_node: u32 },
+    /// A [`CompGraph`] with the default topology.
+    Comp {
+        inside: usize,
+        outside: usize,
+        n_layers: usize,
+    },
+    /// The binary hypercube created with [`hypercube`], `n` has to be less than `32`.
+    Hypercube { n: u32 },
+    /// A `G(n, p)` graph created with [`RandomGraph::erdos_renyi`].
+    ErdosRenyi { num_nodes: u32, p: f64, seed: u64 },
+}
+
+impl GraphSpec {
+    /// Checks that [`generate`] can create the graph, by calling the fallible constructor
+    /// of the generator where there is one.
+    ///
+    /// Returns the error of the constructor, like [`GraphError::InfeasibleEdgeCount`] if a
+    /// random graph can not have that many edges, or [`GraphError::Overflow`] if the
+    /// dimension of a hypercube is `32` or more.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        match *self {
+            GraphSpec::Cube {
+                width,
+                height,
+                depth,
+                timesteps,
+            } => CubeGraph::try_new(width, height, depth, timesteps).map(drop),
+            GraphSpec::Random {
+                num_nodes,
+                num_edges,
+                seed,
+            } => RandomGraph::try_with_num_nodes(num_nodes, num_edges, seed).map(drop),
+            GraphSpec::Layered {
+                num_nodes,
+                edges_per_node,
+            } => LayeredGraph::try_new_from_num_nodes(num_nodes, edges_per_node).map(drop),
+            GraphSpec::Hypercube { n } => 1u32.checked_shl(n).map(drop).ok_or(GraphError::Overflow),
+            GraphSpec::Comp { .. } | GraphSpec::ErdosRenyi { .. } => Ok(()),
+        }
+    }
+}
+
+/// Parses a spec written as its kind followed by `key=value` pairs, separated by whitespace,
+/// like `cube width=3 height=3 depth=3 timesteps=2`.
+///
+/// The kinds are `cube`, `random`, `layered`, `comp`, `hypercube` and `erdos_renyi`, and the
+/// keys are the names of the fields of the variant. Every field has to be given exactly once.
+/// Specs which fail [`GraphSpec::validate`] are rejected as well.
+impl FromStr for GraphSpec {
+    type Err = GraphError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| GraphError::InvalidSpec(reason.to_string());
+        let mut words = s.split_whitespace();
+        let kind = words.next().ok_or_else(|| invalid("the kind is missing"))?;
+        let mut params = BTreeMap::new();
+        for word in words {
+            let (key, value) = word
+                .split_once('=')
+                .ok_or_else(|| invalid(&format!("expected key=value, found '{}'", word)))?;
+            if params.insert(key, value).is_some() {
+                return Err(invalid(&format!("'{}' is given twice", key)));
+            }
+        }
+
+        fn take<T: FromStr>(params: &mut BTreeMap<&str, &str>, key: &str) -> Result<T, GraphError> {
+            let value = params
+                .remove(key)
+                .ok_or_else(|| GraphError::InvalidSpec(format!("'{}' is missing", key)))?;
+            value
+                .parse()
+                .map_err(|_| GraphError::InvalidSpec(format!("invalid {} '{}'", key, value)))
+        }
+        let params = &mut params;
+        let spec = match kind {
+            "cube" => GraphSpec::Cube {
+                width: take(params, "width")?,
+                height: take(params, "height")?,
+                depth: take(params, "depth")?,
+                timesteps: take(params, "timesteps")?,
+            },
+            "random" => GraphSpec::Random {
+                num_nodes: take(params, "num_nodes")?,
+                num_edges: take(params, "num_edges")?,
+                seed: take(params, "seed")?,
+            },
+            "layered" => GraphSpec::Layered {
+                num_nodes: take(params, "num_nodes")?,
+                edges_per_node: take(params, "edges_per_node")?,
+            },
+            "comp" => GraphSpec::Comp {
+                inside: take(params, "inside")?,
+                outside: take(params, "outside")?,
+                n_layers: take(params, "n_layers")?,
+            },
+            "hypercube" => GraphSpec::Hypercube {
+                n: take(params, "n")?,
+            },
+            "erdos_renyi" => GraphSpec::ErdosRenyi {
+                num_nodes: take(params, "num_nodes")?,
+                p: take(params, "p")?,
+                seed: take(params, "seed")?,
+            },
+            _ => return Err(invalid(&format!("unknown kind '{}'", kind))),
+        };
+        if let Some(key) = params.keys().next() {
+            return Err(invalid(&format!("unknown key '{}'", key)));
+        }
+        spec.validate().map_err(|err| invalid(&err.to_string()))?;
+        Ok(spec)
+    }
+}
+
+/// Create the graph described by `spec`.
+///
+/// The number of nodes is the one of the generator, so nodes without edges are kept.
+/// The generators may panic or never finish for specs which fail [`GraphSpec::validate`].
+pub fn generate(spec: GraphSpec) -> Graph {
+    let into_u32 = |edges: Vec<(usize, usize)>| {
+        edges
+            .into_iter()
+            .map(|(tail, head)| (tail as u32, head as u32))
+            .collect()
+    };
+
+    match spec {
+        GraphSpec::Cube {
+            width,
+            height,
+            depth,
+            timesteps,
+        } => {
+            let cube = CubeGraph::new(width, height, depth, timesteps);
+            Graph::new(into_u32(cube.build()), cube.node_count())
+        }
+        GraphSpec::Random {
+            num_nodes,
+            num_edges,
+            seed,
+        } => {
+            let random = RandomGraph::with_num_nodes(num_nodes, num_edges, seed);
+            Graph::new(random.build_edges(), num_nodes as usize)
+        }
+        GraphSpec::Layered {
+            num_nodes,
+            edges_per_node,
+        } => {
+            let layered = LayeredGraph::new_from_num_nodes(num_nodes, edges_per_node);
+            Graph::new(layered.build_edges(), num_nodes as usize)
+        }
+        GraphSpec::Comp {
+            inside,
+            outside,
+            n_layers,
+        } => {
+            let edges = CompGraph::new(inside, outside, n_layers).build();
+            // one comm vertex between every two layers
+            let n_comm = if outside > 0 {
+                n_layers.saturating_sub(1)
+            } else {
+                0
+            };
+            Graph::new(into_u32(edges), (inside + outside) * n_layers + n_comm)
+        }
+        GraphSpec::Hypercube { n } => Graph::new(hypercube(n), 1 << n),
+        GraphSpec::ErdosRenyi { num_nodes, p, seed } => Graph::new(
+            RandomGraph::erdos_renyi(num_nodes, p, seed),
+            num_nodes as usize,
+        ),
+    }
+}
+
+/// Assembles a single graph out of several blocks, which are connected at their interface nodes.
+///
+/// Blocks are referred to by the order in which they were added, starting at zero,
+/// nodes are referred to by their id inside of the block.
+/// The ids of every block get offset by the number of nodes of all blocks added before it.
+///
+/// Example Usage:
+///
+/// ```
+/// use graph_generator::comm::{comp_graph, CubeGraph};
+/// use graph_generator::graph::CompositeBuilder;
+///
+/// let graph = CompositeBuilder::new()
+///     .add_block(comp_graph(2, 1, 3))
+///     .add_block(CubeGraph::new(3, 3, 3, 2).build())
+///     .connect((0, 6), (1, 0)) // connect the last layer of block 0 to the cube
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct CompositeBuilder {
+    blocks: Vec<Graph>,
+    connections: Vec<((usize, u32), (usize, u32))>,
+}
+
+impl CompositeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block to the graph.
+    pub fn add_block(mut self, block: impl Into<Graph>) -> Self {
+        self.blocks.push(block.into());
+        self
+    }
+
+    /// Add an edge between two blocks, with both ends given as `(block, node)` pairs.
+    pub fn connect(mut self, from: (usize, u32), to: (usize, u32)) -> Self {
+        self.connections.push((from, to));
+        self
+    }
+
+    /// Build the graph.
+    ///
+    /// Panics if a connection refers to a block which was not added.
+    pub fn build(self) -> Graph {
+        let mut offsets = Vec::with_capacity(self.blocks.len());
+        let mut graph = Graph::default();
+        for block in &self.blocks {
+            offsets.push(graph.n_nodes as u32);
+            graph.edges = disjoint_union(&graph.edges, graph.n_nodes, &block.edges);
+            graph.n_nodes += block.n_nodes;
+        }
+
+        for ((from, tail), (to, head)) in self.connections {
+            graph.edges.push((offsets[from] + tail, offsets[to] + head));
+        }
+
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comm::comp_graph;
+    use crate::random::RandomGraph;
+    use crate::topology::cycle_graph;
+
+    #[test]
+    fn test_from_edges() {
+        let graph = Graph::from_edges(vec![(0, 3), (2, 1)]);
+        assert_eq!(graph.n_nodes, 4);
+        assert_eq!(Graph::from_edges(Vec::new()).n_nodes, 0);
+    }
+
+    #[test]
+    fn test_generate() {
+        let cube = generate(GraphSpec::Cube {
+            width: 3,
+            height: 3,
+            depth: 3,
+            timesteps: 2,
+        });
+        assert_eq!(cube.n_nodes, 55);
+        assert_eq!(cube.edges.len(), CubeGraph::new(3, 3, 3, 2).build().len());
+
+        let random = generate(GraphSpec::Random {
+            num_nodes: 30,
+            num_edges: 20,
+            seed: 1,
+        });
+        assert_eq!((random.n_nodes, random.edges.len()), (30, 20));
+
+        let layered = generate(GraphSpec::Layered {
+            num_nodes: 16,
+            edges_per_node: 3,
+        });
+        assert_eq!(layered.n_nodes, 16);
+
+        let comp = generate(GraphSpec::Comp {
+            inside: 3,
+            outside: 2,
+            n_layers: 4,
+        });
+        assert_eq!(comp.n_nodes, 23);
+        assert_eq!(Graph::from_edges(comp.edges).n_nodes, 23);
+
+        assert_eq!(generate(GraphSpec::Hypercube { n: 3 }).n_nodes, 8);
+
+        let erdos_renyi = generate(GraphSpec::ErdosRenyi {
+            num_nodes: 12,
+            p: 1.,
+            seed: 1,
+        });
+        assert_eq!((erdos_renyi.n_nodes, erdos_renyi.edges.len()), (12, 66));
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = "cube width=3 height=3 depth=3 timesteps=2".parse::<GraphSpec>();
+        let cube = generate(spec.unwrap());
+        assert_eq!(cube.n_nodes, 55);
+
+        // the order of the keys and the whitespace don't matter
+        let spec = "erdos_renyi\n  seed=4 p=0.5\n  num_nodes=20\n".parse::<GraphSpec>();
+        let expected = GraphSpec::ErdosRenyi {
+            num_nodes: 20,
+            p: 0.5,
+            seed: 4,
+        };
+        assert_eq!(spec.unwrap(), expected);
+        assert_eq!(generate(expected).n_nodes, 20);
+        assert_eq!(
+            "comp inside=3 outside=2 n_layers=4"
+                .parse::<GraphSpec>()
+                .unwrap(),
+            GraphSpec::Comp {
+                inside: 3,
+                outside: 2,
+                n_layers: 4
+            }
+        );
+
+        let invalid = [
+            "",
+            "torus n=3",
+            "hypercube",
+            "hypercube n=3 m=2",
+            "hypercube n=3 n=3",
+            "hypercube n=three",
+            "hypercube n 3",
+            "hypercube n=40",
+            "random num_nodes=3 num_edges=10 seed=1",
+            "layered num_nodes=5 edges_per_node=0",
+            "layered num_nodes=5 edges_per_node=1",
+            "layered num_nodes=4294967295 edges_per_node=2",
+            "cube width=0 height=3 depth=3 timesteps=2",
+        ];
+        for spec in invalid {
+            assert!(matches!(
+                spec.parse::<GraphSpec>(),
+                Err(GraphError::InvalidSpec(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let graph = Graph::new(vec![(0, 1), (0, 2), (1, 2), (0, 3)], 5);
+        let stats = GraphStats {
+            n_nodes: 5,
+            n_edges: 4,
+            min_out_degree: 0,
+            max_out_degree: 3,
+            min_in_degree: 0,
+            max_in_degree: 2,
+        };
+        assert_eq!(graph.stats(), stats);
+        assert_eq!(Graph::default().stats(), GraphStats::default());
+    }
+
+    #[test]
+    fn test_is_dag() {
+        let random = Graph::from_edges(RandomGraph::with_num_nodes(20, 40, 3).build_edges());
+        assert!(random.is_dag());
+        assert!(!Graph::from_edges(cycle_graph(5)).is_dag());
+    }
+
+    #[test]
+    fn test_add_node_and_edge() {
+        let mut graph = Graph::default();
+        graph.add_edge(0, 2);
+        assert_eq!(graph.node_ids(), 0..3);
+
+        let isolated = graph.add_node();
+        assert_eq!(isolated, 3);
+        assert_eq!(graph.node_ids(), 0..4);
+
+        let filename = std::env::temp_dir().join("graph_generator_isolated.dot");
+        let filename = filename.to_str().unwrap();
+        crate::io::write_dot(filename, &graph.edges, graph.n_nodes).unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        assert!(content.lines().any(|line| line.trim() == "3;"));
+    }
+
+    #[test]
+    fn test_composite_builder() {
+        let first = comp_graph(2, 1, 2); // 6 nodes + 1 comm vertex
+        let second = vec![(0u32, 1u32), (1, 2)];
+        let graph = CompositeBuilder::new()
+            .add_block(first.clone())
+            .add_block(second)
+            .connect((0, 5), (1, 0))
+            .build();
+
+        assert_eq!(graph.n_nodes, 7 + 3);
+        assert_eq!(graph.edges.len(), first.len() + 2 + 1);
+        assert!(graph.edges.contains(&(7, 8)));
+        assert!(graph.edges.contains(&(8, 9)));
+        assert_eq!(graph.edges.last(), Some(&(5, 7)));
+    }
+}
@@ -0,0 +1,497 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use crate::error::GraphError;
+use crate::graph::Graph;
+use crate::util::{compact_ids, connected_components};
+use crate::WeightFormat;
+
+/// Write the edges of a graph to a file in the DOT format.
+///
+/// All nodes in `0..n_nodes` are declared, so isolated nodes are kept.
+pub fn write_dot(filename: &str, edges: &[(u32, u32)], n_nodes: usize) -> Result<(), GraphError> {
+    let mut file = File::create(filename)?;
+
+    let mut buffer = String::from("digraph {\n");
+    (0..n_nodes).for_each(|node| buffer.push_str(&format!("    {};\n", node)));
+    edges
+        .iter()
+        .for_each(|(tail, head)| buffer.push_str(&format!("    {} -> {};\n", tail, head)));
+    buffer.push_str("}\n");
+
+    file.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
+/// Read the edges of a graph from a DOT file.
+///
+/// Only a small subset of DOT is supported: a single `digraph { ... }` containing
+/// node statements `a;` and edge statements `a -> b;`, where nodes are numeric ids.
+/// Attributes in `[...]` are ignored, anything else results in an error.
+pub fn read_dot(filename: &str) -> Result<Vec<(u32, u32)>, GraphError> {
+    let content = std::fs::read_to_string(filename)?;
+    Ok(parse_dot(&content)?)
+}
+
+/// Write a map of node ids, as returned by [`compact_ids`](crate::util::compact_ids),
+/// to a text file.
+///
+/// Every line has the form `new_id old_id`.
+pub fn write_id_map(filename: &str, map: &[u32]) -> Result<(), GraphError> {
+    let mut file = File::create(filename)?;
+
+    let buffer = map
+        .iter()
+        .enumerate()
+        .map(|(new, old)| format!("{} {}\n", new, old))
+        .collect::<String>();
+    file.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
+/// Read a map of node ids written by [`write_id_map`].
+/// The entry at index `i` of the result is the old id of node `i`.
+pub fn read_id_map(filename: &str) -> Result<Vec<u32>, GraphError> {
+    let content = std::fs::read_to_string(filename)?;
+
+    let mut map = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let (new, old) = line
+            .split_once(' ')
+            .ok_or_else(|| invalid_data(&format!("invalid line '{}'", line)))?;
+        if parse_id(new)? as usize != map.len() {
+            return Err(invalid_data("ids of the map are not contiguous").into());
+        }
+        map.push(parse_id(old)?);
+    }
+
+    Ok(map)
+}
+
+/// Write every weakly connected component of a graph to its own file `dir/component_{i}.txt`,
+/// in the format of [`write_to_file`](crate::write_to_file), with the ids compacted
+/// by [`compact_ids`]. The components are ordered by their lowest node.
+///
+/// Nodes without edges are skipped. Returns the number of files written, or an error
+/// of kind [`InvalidInput`](io::ErrorKind::InvalidInput) if an edge has an id of at least
+/// `n_nodes`.
+pub fn write_components(
+    dir: &str,
+    edges: &[(u32, u32)],
+    n_nodes: usize,
+) -> Result<usize, GraphError> {
+    if let Some((tail, head)) = edges
+        .iter()
+        .find(|(tail, head)| *tail.max(head) as usize >= n_nodes)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the edge {} -> {} is not in a graph of {} nodes",
+                tail, head, n_nodes
+            ),
+        )
+        .into());
+    }
+    let components = connected_components(edges, n_nodes);
+    let mut component_of = vec![0; n_nodes];
+    for (i, component) in components.iter().enumerate() {
+        for node in component {
+            component_of[*node as usize] = i;
+        }
+    }
+    let mut component_edges = vec![Vec::new(); components.len()];
+    for edge in edges {
+        component_edges[component_of[edge.0 as usize]].push(*edge);
+    }
+
+    let mut n_files = 0;
+    for edges in component_edges.iter().filter(|edges| !edges.is_empty()) {
+        let filename = std::path::Path::new(dir).join(format!("component_{}.txt", n_files));
+        let filename = filename
+            .to_str()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid directory"))?;
+        crate::write_to_file(filename, &compact_ids(edges).0)?;
+        n_files += 1;
+    }
+
+    Ok(n_files)
+}
+
+/// Write a graph to a file in the GraphML format, with an integer `timestep` attribute
+/// for every node and a double `weight` attribute for every edge.
+///
+/// `node_timesteps[i]` is the timestep of node `i` and `edge_weights[i]` the weight of
+/// `graph.edges[i]`, which is formatted according to `format`.
+/// Returns an error if their lengths do not match the graph.
+pub fn write_graphml_attributed(
+    filename: &str,
+    graph: &Graph,
+    node_timesteps: &[u32],
+    edge_weights: &[f64],
+    format: WeightFormat,
+) -> Result<(), GraphError> {
+    if node_timesteps.len() != graph.n_nodes || edge_weights.len() != graph.edges.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the attributes do not match the nodes and edges of the graph",
+        )
+        .into());
+    }
+    let mut file = File::create(filename)?;
+
+    let mut buffer = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    buffer.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    buffer.push_str(
+        "  <key id=\"timestep\" for=\"node\" attr.name=\"timestep\" attr.type=\"int\"/>\n",
+    );
+    buffer.push_str(
+        "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+    );
+    buffer.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+    for (node, timestep) in node_timesteps.iter().enumerate() {
+        buffer.push_str(&format!(
+            "    <node id=\"n{}\"><data key=\"timestep\">{}</data></node>\n",
+            node, timestep
+        ));
+    }
+    for ((tail, head), weight) in graph.edges.iter().zip(edge_weights) {
+        buffer.push_str(&format!(
+            "    <edge source=\"n{}\" target=\"n{}\"><data key=\"weight\">{}</data></edge>\n",
+            tail,
+            head,
+            format.format(*weight)
+        ));
+    }
+    buffer.push_str("  </graph>\n</graphml>\n");
+
+    file.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
+/// Write the weighted edges of a graph to a file in the Matrix Market coordinate format,
+/// as a real `n_nodes x n_nodes` adjacency matrix.
+///
+/// After the banner and the size line, every edge is written as `row col value`,
+/// where `row` is the tail and `col` the head of the edge, both one-indexed,
+/// and `value` the weight formatted according to `format`.
+pub fn write_matrix_market_real(
+    filename: &str,
+    edges: &[(u32, u32, f64)],
+    n_nodes: usize,
+    format: WeightFormat,
+) -> Result<(), GraphError> {
+    let mut file = File::create(filename)?;
+
+    let mut buffer = String::from("%%MatrixMarket matrix coordinate real general\n");
+    buffer.push_str(&format!("{} {} {}\n", n_nodes, n_nodes, edges.len()));
+    edges.iter().for_each(|(tail, head, weight)| {
+        buffer.push_str(&format!(
+            "{} {} {}\n",
+            tail + 1,
+            head + 1,
+            format.format(*weight)
+        ))
+    });
+
+    file.write_all(buffer.as_bytes())?;
+    Ok(())
+}
+
+/// Identifies files written by [`write_binary`].
+const BINARY_MAGIC: [u8; 4] = *b"GGEL";
+const BINARY_VERSION: u32 = 1;
+/// Magic, version, number of nodes and number of edges.
+const BINARY_HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+/// Write the edges of a graph to a file in a compact binary format.
+///
+/// The file starts with a header consisting of the magic bytes `GGEL`, the format version
+/// as `u32`, the number of nodes as `u64` and the number of edges as `u64`, followed by
+/// the tail and head of every edge as `u32`. All numbers are little endian.
+pub fn write_binary(
+    filename: &str,
+    edges: &[(u32, u32)],
+    n_nodes: usize,
+) -> Result<(), GraphError> {
+    let mut file = File::create(filename)?;
+
+    let mut buffer = Vec::with_capacity(BINARY_HEADER_LEN + edges.len() * 8);
+    buffer.extend_from_slice(&BINARY_MAGIC);
+    buffer.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+    buffer.extend_from_slice(&(n_nodes as u64).to_le_bytes());
+    buffer.extend_from_slice(&(edges.len() as u64).to_le_bytes());
+    for (tail, head) in edges {
+        buffer.extend_from_slice(&tail.to_le_bytes());
+        buffer.extend_from_slice(&head.to_le_bytes());
+    }
+
+    file.write_all(&buffer)?;
+    Ok(())
+}
+
+/// Read the edges and the number of nodes of a graph written by [`write_binary`].
+///
+/// Returns an error if the file does not start with the magic bytes, has an unknown
+/// version or does not contain the number of edges given in the header.
+pub fn read_binary(filename: &str) -> Result<(Vec<(u32, u32)>, usize), GraphError> {
+    let content = std::fs::read(filename)?;
+    Ok(parse_binary(&content)?)
+}
+
+fn parse_binary(content: &[u8]) -> io::Result<(Vec<(u32, u32)>, usize)> {
+    if content.len() < BINARY_HEADER_LEN || content[..4] != BINARY_MAGIC {
+        return Err(invalid_data("not a binary edge file"));
+    }
+    let u32_at = |i: usize| u32::from_le_bytes(content[i..i + 4].try_into().unwrap());
+    let u64_at = |i: usize| u64::from_le_bytes(content[i..i + 8].try_into().unwrap());
+
+    if u32_at(4) != BINARY_VERSION {
+        return Err(invalid_data(&format!("unsupported version {}", u32_at(4))));
+    }
+    let n_nodes = u64_at(8) as usize;
+    let n_edges = u64_at(16) as usize;
+    let body = &content[BINARY_HEADER_LEN..];
+    if Some(body.len()) != n_edges.checked_mul(8) {
+        return Err(invalid_data("number of edges does not match the header"));
+    }
+
+    let edges = (0..n_edges)
+        .map(|i| BINARY_HEADER_LEN + 8 * i)
+        .map(|start| (u32_at(start), u32_at(start + 4)))
+        .collect();
+    Ok((edges, n_nodes))
+}
+
+fn parse_dot(content: &str) -> io::Result<Vec<(u32, u32)>> {
+    let body = content
+        .trim()
+        .strip_prefix("digraph")
+        .and_then(|rest| rest.trim_start().strip_prefix('{'))
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| invalid_data("expected 'digraph { ... }'"))?;
+
+    let mut edges = Vec::new();
+    for statement in body.split([';', '\n']) {
+        let statement = strip_attributes(statement)?;
+        if statement.is_empty() {
+            continue;
+        }
+
+        match statement.split_once("->") {
+            Some((tail, head)) => edges.push((parse_id(tail)?, parse_id(head)?)),
+            // node statement, nodes are implied by the edges
+            None => {
+                parse_id(statement)?;
+            }
+        }
+    }
+
+    Ok(edges)
+}
+
+/// Removes a trailing attribute list `[...]` from a statement.
+fn strip_attributes(statement: &str) -> io::Result<&str> {
+    match statement.find('[') {
+        Some(start) if statement.trim_end().ends_with(']') => Ok(statement[..start].trim()),
+        Some(_) => Err(invalid_data("unterminated attribute list")),
+        None => Ok(statement.trim()),
+    }
+}
+
+fn parse_id(id: &str) -> io::Result<u32> {
+    id.trim()
+        .parse()
+        .map_err(|_| invalid_data(&format!("unsupported node id '{}'", id.trim())))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_dot_round_trip() {
+        let edges = vec![(0, 1), (1, 2), (0, 3), (3, 2)];
+        let filename = std::env::temp_dir().join("graph_generator_round_trip.dot");
+        let filename = filename.to_str().unwrap();
+
+        write_dot(filename, &edges, 5).unwrap();
+        let actual = read_dot(filename).unwrap();
+
+        assert_eq!(
+            actual.into_iter().collect::<HashSet<_>>(),
+            edges.into_iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_id_map_round_trip() {
+        let (edges, map) = crate::util::compact_ids(&[(3, 10), (10, 42)]);
+        let filename = std::env::temp_dir().join("graph_generator_id_map.txt");
+        let filename = filename.to_str().unwrap();
+
+        write_id_map(filename, &map).unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        assert_eq!(content.lines().count(), 3);
+        assert_eq!(edges, vec![(0, 1), (1, 2)]);
+        assert_eq!(read_id_map(filename).unwrap(), map);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let cube = crate::comm::CubeGraph::new(3, 3, 3, 3);
+        let edges = cube
+            .window_edges(0, 1)
+            .into_iter()
+            .map(|(t, h)| (t as u32, h as u32))
+            .collect::<Vec<_>>();
+        let filename = std::env::temp_dir().join("graph_generator_round_trip.bin");
+        let filename = filename.to_str().unwrap();
+
+        write_binary(filename, &edges, cube.node_count()).unwrap();
+        let (actual, n_nodes) = read_binary(filename).unwrap();
+
+        assert_eq!(n_nodes, cube.node_count());
+        assert_eq!(
+            actual.into_iter().collect::<HashSet<_>>(),
+            edges.into_iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_components() {
+        let dir = std::env::temp_dir().join("graph_generator_components");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        let dir = dir.to_str().unwrap();
+        // two disjoint triangles and an isolated node
+        let edges = [(0, 2), (2, 5), (0, 5), (1, 3), (3, 4), (1, 4)];
+
+        assert_eq!(write_components(dir, &edges, 7).unwrap(), 2);
+        let first = crate::read_from_file(&format!("{}/component_0.txt", dir)).unwrap();
+        assert_eq!(first, vec![(0, 1), (1, 2), (0, 2)]);
+        let second = crate::read_from_file(&format!("{}/component_1.txt", dir)).unwrap();
+        assert_eq!(second, first);
+        assert!(!std::path::Path::new(&format!("{}/component_2.txt", dir)).exists());
+
+        // ids outside of the graph are rejected before anything is written
+        let _ = std::fs::remove_file(format!("{}/component_0.txt", dir));
+        let err = write_components(dir, &[(0, 1), (0, 5)], 2).unwrap_err();
+        assert!(matches!(err, GraphError::Io(err) if err.kind() == io::ErrorKind::InvalidInput));
+        assert!(!std::path::Path::new(&format!("{}/component_0.txt", dir)).exists());
+    }
+
+    #[test]
+    fn test_write_graphml_attributed() {
+        let cube = crate::comm::CubeGraph::new(2, 2, 2, 2);
+        let weighted = cube.build_weighted_with(|_, (_, _, _, ts)| ts as f64 + 0.5);
+        let graph = Graph::from(cube.build());
+        let weights = weighted.iter().map(|(_, _, w)| *w).collect::<Vec<_>>();
+        let mut timesteps = vec![0; cube.node_count()];
+        for (ts, group) in cube.timestep_partition().into_iter().enumerate() {
+            group
+                .into_iter()
+                .for_each(|node| timesteps[node as usize] = ts as u32);
+        }
+        let filename = std::env::temp_dir().join("graph_generator_attributed.graphml");
+        let filename = filename.to_str().unwrap();
+
+        write_graphml_attributed(
+            filename,
+            &graph,
+            &timesteps,
+            &weights,
+            WeightFormat::Shortest,
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "<key id=\"timestep\" for=\"node\" attr.name=\"timestep\" attr.type=\"int\"/>"
+        ));
+        assert!(content.contains(
+            "<key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>"
+        ));
+        // the comm vertex has the timestep before it, the neighbors of the first cell the next one
+        assert!(content
+            .contains("<edge source=\"n0\" target=\"n16\"><data key=\"weight\">0.5</data></edge>"));
+        assert!(content
+            .contains("<edge source=\"n0\" target=\"n9\"><data key=\"weight\">1.5</data></edge>"));
+        assert!(content.contains("<node id=\"n9\"><data key=\"timestep\">1</data></node>"));
+
+        let format = WeightFormat::Fixed(2);
+        write_graphml_attributed(filename, &graph, &timesteps, &weights, format).unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        assert!(content.contains(
+            "<edge source=\"n0\" target=\"n16\"><data key=\"weight\">0.50</data></edge>"
+        ));
+
+        let short_weights = &weights[1..];
+        assert!(
+            write_graphml_attributed(filename, &graph, &timesteps, short_weights, format).is_err()
+        );
+    }
+
+    #[test]
+    fn test_write_matrix_market_real() {
+        let edges = [(0, 1, 0.5), (1, 2, -2.25), (3, 0, 1e-3)];
+        let filename = std::env::temp_dir().join("graph_generator_real.mtx");
+        let filename = filename.to_str().unwrap();
+
+        write_matrix_market_real(filename, &edges, 4, WeightFormat::Shortest).unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next(),
+            Some("%%MatrixMarket matrix coordinate real general")
+        );
+        assert_eq!(lines.next(), Some("4 4 3"));
+
+        let entries = lines
+            .map(|line| {
+                let fields = line.split(' ').collect::<Vec<_>>();
+                let row: u32 = fields[0].parse().unwrap();
+                let col: u32 = fields[1].parse().unwrap();
+                (row - 1, col - 1, fields[2].parse::<f64>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(entries, edges);
+
+        write_matrix_market_real(filename, &edges, 4, WeightFormat::Scientific(2)).unwrap();
+        let content = std::fs::read_to_string(filename).unwrap();
+        assert_eq!(content.lines().nth(4), Some("4 1 1.00e-3"));
+    }
+
+    #[test]
+    fn test_parse_binary_invalid() {
+        let mut content = b"GGEL\x01\0\0\0".to_vec();
+        content.extend_from_slice(&3u64.to_le_bytes());
+        content.extend_from_slice(&1u64.to_le_bytes());
+        content.extend_from_slice(&[0, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(parse_binary(&content).unwrap(), (vec![(0, 2)], 3));
+
+        // truncated
+        assert!(parse_binary(&content[..content.len() - 1]).is_err());
+        // wrong magic
+        content[0] = b'X';
+        assert!(parse_binary(&content).is_err());
+        assert!(parse_binary(b"GGEL").is_err());
+    }
+
+    #[test]
+    fn test_parse_dot_attributes() {
+        let dot = "digraph {\n  0 -> 1 [weight=2];\n  1 [label=\"a\"];\n  1 -> 2\n}";
+        assert_eq!(parse_dot(dot).unwrap(), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_parse_dot_unsupported() {
+        assert!(parse_dot("graph { 0 -- 1; }").is_err());
+        assert!(parse_dot("digraph { rankdir=LR; }").is_err());
+        assert!(parse_dot("digraph { a -> b; }").is_err());
+    }
+}
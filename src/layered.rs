@@ -1,7 +1,12 @@
+use alloc::vec::Vec;
+
+use super::error::GraphError;
+use super::util::Lcg;
+
 /// Creates a graph with `num_nodes` vertices, which have `edges_per_node` edges.
 /// The layout of the graph is layered, where it grows from one vertice to a certain maximum with
 /// and then starts shrinking again to one vertice at the bottom layer:
-/// ```
+/// ```text
 ///             /---v---\
 ///          /-v-\    /-v-\
 ///         v    v   v    v
@@ -26,15 +31,16 @@ impl LayeredGraph {
         num_nodes: u32,
         edges_per_node: u32,
     ) -> Self {
-        return LayeredGraph {
+        LayeredGraph {
             growing_layers: growing_nodes,
             shrinking_layers: shrinking_nodes,
             num_nodes,
             edges_per_node,
-        };
+        }
     }
 
     /// Not implemented.
+    #[allow(dead_code)]
     fn new_from_num_edges(_num_edges: usize, _edges_per_node: usize) -> Self {
         unimplemented!()
     }
@@ -58,13 +64,44 @@ impl LayeredGraph {
         unreachable!()
     }
 
+    /// Creates a layout like [`new_from_num_nodes`](LayeredGraph::new_from_num_nodes),
+    /// but checks the parameters first.
+    ///
+    /// Returns [`GraphError::InvalidDimension`] if `edges_per_node` is less than `2`,
+    /// and [`GraphError::Overflow`] if the size of the layers does not fit into a `u32`.
+    pub fn try_new_from_num_nodes(num_nodes: u32, edges_per_node: u32) -> Result<Self, GraphError> {
+        if edges_per_node < 2 {
+            return Err(GraphError::InvalidDimension {
+                name: "edges_per_node",
+                value: edges_per_node as usize,
+            });
+        }
+        let calc_num_nodes = |pow: u32| {
+            edges_per_node
+                .checked_pow(pow)
+                .map(|n| (n - 1) / (edges_per_node - 1))
+        };
+        for i in 0.. {
+            let total_nodes = calc_num_nodes(i)
+                .zip(calc_num_nodes(i + 1))
+                .and_then(|(growing_nodes, shrinking_nodes)| {
+                    growing_nodes.checked_add(shrinking_nodes)
+                })
+                .ok_or(GraphError::Overflow)?;
+            if total_nodes >= num_nodes {
+                return Ok(Self::new_from_num_nodes(num_nodes, edges_per_node));
+            }
+        }
+        unreachable!()
+    }
+
     /// Build the edges of the graph.
     pub fn build_edges(&self) -> Vec<(u32, u32)> {
         // start with node = 0
         let mut edges = Vec::new();
         let mut node = 0;
         for layer in 0..self.growing_layers {
-            let layer_size = self.edges_per_node.pow(layer as u32);
+            let layer_size = self.edges_per_node.pow(layer);
             for _ in 0..layer_size {
                 for edge in 1..=self.edges_per_node {
                     edges.push((node, self.edges_per_node * node + edge));
@@ -77,7 +114,7 @@ impl LayeredGraph {
         }
 
         for layer in (1..self.shrinking_layers).rev() {
-            let mut layer_size = self.edges_per_node.pow(layer as u32);
+            let mut layer_size = self.edges_per_node.pow(layer);
             for _ in 0..(layer_size / self.edges_per_node) {
                 for edge in 0..self.edges_per_node {
                     let successor = node + layer_size - edge;
@@ -92,12 +129,90 @@ impl LayeredGraph {
         }
         edges
     }
+
+    /// Get the layer of the node `id`, where node `0` is in layer `0`.
+    /// Returns `None` if the graph has no node `id`.
+    ///
+    /// The layers have `1, k, ..., k^g` nodes while growing and then shrink back to `1` node,
+    /// where `k` is `edges_per_node` and `g` is the number of growing layers.
+    pub fn layer_of(&self, id: u32) -> Option<usize> {
+        if id >= self.num_nodes {
+            return None;
+        }
+
+        let k = self.edges_per_node;
+        let growing = (0..=self.growing_layers).map(|layer| k.saturating_pow(layer));
+        let shrinking = (1..self.shrinking_layers)
+            .rev()
+            .map(|layer| k.saturating_pow(layer - 1));
+        let mut first_id = 0u32;
+        for (layer, size) in growing.chain(shrinking).enumerate() {
+            first_id = first_id.saturating_add(size);
+            if id < first_id {
+                return Some(layer);
+            }
+        }
+        None
+    }
+
+    /// Build the edges of the graph with random weights, where the weight of an edge is
+    /// drawn uniformly from the range `(min, max)` returned by `weight_for_layer` for
+    /// the layer of its tail.
+    pub fn build_weighted_by_layer(
+        &self,
+        seed: u64,
+        weight_for_layer: impl Fn(usize) -> (f64, f64),
+    ) -> Vec<(u32, u32, f64)> {
+        let mut lcg = Lcg::with_seed(seed);
+        self.build_edges()
+            .into_iter()
+            .map(|(tail, head)| {
+                // every tail is a node of the graph, so it has a layer
+                let (min, max) = weight_for_layer(self.layer_of(tail).unwrap_or_default());
+                (tail, head, min + (max - min) * lcg.next_f64())
+            })
+            .collect()
+    }
+
+    /// Build the dense adjacency matrix of the graph, where `matrix[tail][head]` is `1` if
+    /// there is an edge from `tail` to `head`.
+    ///
+    /// Since edges always go from lower to higher ids, the matrix is strictly upper triangular.
+    pub fn to_adjacency_matrix(&self) -> Vec<Vec<u8>> {
+        let mut matrix = vec![vec![0; self.num_nodes as usize]; self.num_nodes as usize];
+        for (tail, head) in self.build_edges() {
+            matrix[tail as usize][head as usize] = 1;
+        }
+        matrix
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::GraphError;
     use crate::layered::LayeredGraph;
 
+    #[test]
+    fn test_try_new_from_num_nodes() {
+        assert_eq!(
+            LayeredGraph::try_new_from_num_nodes(8, 2).unwrap(),
+            LayeredGraph::new_from_num_nodes(8, 2)
+        );
+        for edges_per_node in [0, 1] {
+            assert!(matches!(
+                LayeredGraph::try_new_from_num_nodes(5, edges_per_node),
+                Err(GraphError::InvalidDimension {
+                    name: "edges_per_node",
+                    ..
+                })
+            ));
+        }
+        assert!(matches!(
+            LayeredGraph::try_new_from_num_nodes(u32::MAX, 2),
+            Err(GraphError::Overflow)
+        ));
+    }
+
     #[test]
     fn test_new_from_num_nodes_2_nodes_2_edges() {
         let expected = LayeredGraph::new(1, 2, 2, 2);
@@ -126,6 +241,59 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_layer_of() {
+        // layers of 1, 3, 9, 3 and 1 nodes
+        let layout = LayeredGraph::new_from_num_nodes(17, 3);
+        let boundaries = [(0, 0), (1, 1), (3, 1), (4, 2), (12, 2), (13, 3), (15, 3), (16, 4)];
+        for (id, layer) in boundaries {
+            assert_eq!(layout.layer_of(id), Some(layer));
+        }
+        assert_eq!(layout.layer_of(17), None);
+
+        let edges = layout.build_edges();
+        let layers = crate::util::longest_path_layers(&edges, 17).unwrap();
+        for id in 0..17 {
+            assert_eq!(layout.layer_of(id), Some(layers[id as usize]));
+        }
+    }
+
+    #[test]
+    fn test_build_weighted_by_layer() {
+        let layout = LayeredGraph::new_from_num_nodes(17, 3);
+        let last_layer = 3;
+        let range = |layer: usize| (layer as f64 * 10., layer as f64 * 10. + 1.);
+        let edges = layout.build_weighted_by_layer(4, range);
+        assert_eq!(edges.len(), layout.build_edges().len());
+
+        for (tail, _, weight) in &edges {
+            let (min, max) = range(layout.layer_of(*tail).unwrap());
+            assert!(min <= *weight && *weight < max);
+        }
+        assert!(edges.iter().any(|(tail, _, w)| *tail == 0 && *w < 1.));
+        assert!(edges
+            .iter()
+            .any(|(tail, _, w)| layout.layer_of(*tail) == Some(last_layer) && *w >= 30.));
+    }
+
+    #[test]
+    fn test_to_adjacency_matrix() {
+        for (num_nodes, edges_per_node) in [(22, 2), (16, 3), (30, 3)] {
+            let layout = LayeredGraph::new_from_num_nodes(num_nodes, edges_per_node);
+            let matrix = layout.to_adjacency_matrix();
+            let edges = layout.build_edges();
+
+            assert_eq!(matrix.len(), num_nodes as usize);
+            for (tail, row) in matrix.iter().enumerate() {
+                for (head, entry) in row.iter().enumerate() {
+                    assert!(*entry == 0 || tail < head);
+                    let is_edge = edges.contains(&(tail as u32, head as u32));
+                    assert_eq!(*entry == 1, is_edge);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_print_edges() {
         let layout = LayeredGraph::new_from_num_nodes(766, 2);
@@ -1,3 +1,6 @@
+use alloc::vec::Vec;
+
+use super::error::GraphError;
 use super::util::Lcg;
 
 /*********************************************************
@@ -9,7 +12,7 @@ use super::util::Lcg;
 /// A Layered Graph Generator is used to create a graph in
 /// the form of:
 ///
-/// ```
+/// ```text
 /// Layer     Layer(relative)   Tree half     Graph     
 /// 1         1                 Top----|           /0\
 /// 2         2                        |       /1\     /2\
@@ -38,7 +41,7 @@ use super::util::Lcg;
 /// ```
 /// use graph_generator::layered_random::*;
 /// // Create a GraphGenerator with 5 layers, a user defined seed of degree 3
-/// let g: LayeredRandomGraph = LayeredRandomGraph::new(5)
+/// let g: LayeredRandomGraphRandomizer = LayeredRandomGraph::new(5)
 ///     .with_seed(123456u128)
 ///     .with_degree(3);
 /// ```
@@ -62,6 +65,25 @@ impl LayeredRandomGraph {
         self
     }
 
+    /// Creates the randomizer like [`with_degree`](LayeredRandomGraph::with_degree), but checks
+    /// the parameters first.
+    ///
+    /// Returns [`GraphError::InvalidDimension`] if the degree is less than `2`, and
+    /// [`GraphError::TooFewLayers`] if there are no layers or only two, which are not
+    /// enough for both halves.
+    pub fn try_with_degree(self, deg: usize) -> Result<LayeredRandomGraphRandomizer, GraphError> {
+        if deg < 2 {
+            return Err(GraphError::InvalidDimension {
+                name: "degree",
+                value: deg,
+            });
+        }
+        if self.n == 0 || self.n == 2 {
+            return Err(GraphError::TooFewLayers { layers: self.n });
+        }
+        Ok(self.with_degree(deg))
+    }
+
     pub fn with_degree(self, deg: usize) -> LayeredRandomGraphRandomizer {
         // Divide graph into two halfs, and 'glue' them together
         let n_layers_half = self.n.div_ceil(2); // number of layers of one half
@@ -80,7 +102,7 @@ impl LayeredRandomGraph {
                 .map(|(j, i)| (total_vertices - i - 1, total_vertices - j - 1)),
         );
 
-        if self.n % 2 == 0 {
+        if self.n.is_multiple_of(2) {
             edges.extend((pow - deg + 1..).take(pow).map(|i| (i, i + pow)));
         }
 
@@ -91,7 +113,7 @@ impl LayeredRandomGraph {
             n_vertices: total_vertices,
             lcg: match self.seed {
                 Some(seed) => Lcg::new_seed(seed),
-                None => Lcg::new(),
+                None => Lcg::default(),
             },
         }
     }
@@ -121,7 +143,7 @@ impl LayeredRandomGraph {
 /// ```
 /// use graph_generator::layered_random::*;
 /// // a randomizer can only be created through a LayeredGraphGenerator
-/// let graph_randomizer = LayeredRandomGraph::new().with_degree(2);
+/// let graph_randomizer = LayeredRandomGraph::new(5).with_degree(2);
 /// // randomize the graph and get the edges.
 /// let edges: Vec<(usize, usize)> = graph_randomizer
 ///                 .add_random_edge()               // add a random edge on a random layer
@@ -143,6 +165,65 @@ impl LayeredRandomGraphRandomizer {
         self.edges
     }
 
+    /// Build a graph with the same layers, but where every vertex gets connected to
+    /// `min(fan_out, size of the next layer)` distinct random vertices of the next layer,
+    /// instead of the regular tree edges.
+    ///
+    /// Edges added with the randomizing functions before are discarded.
+    pub fn build_with_fan_out(mut self, fan_out: usize) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        for layer in 0..self.n.saturating_sub(1) {
+            let (n_upper, upper_start) =
+                self.determine_vertex_indices(self.determine_relative_layer(layer));
+            let (n_lower, lower_start) =
+                self.determine_vertex_indices(self.determine_relative_layer(layer + 1));
+
+            let mut targets = (lower_start..lower_start + n_lower).collect::<Vec<_>>();
+            for tail in upper_start..upper_start + n_upper {
+                // partial fisher-yates shuffle, the first entries are the chosen targets
+                let n_targets = fan_out.min(n_lower);
+                for i in 0..n_targets {
+                    let j = i + self.lcg.generate_range(n_lower - i);
+                    targets.swap(i, j);
+                }
+                edges.extend(targets[..n_targets].iter().map(|head| (tail, *head)));
+            }
+        }
+
+        edges
+    }
+
+    /// Build a graph with the same layers, which is guaranteed to be weakly connected.
+    ///
+    /// First every vertex, except the one of the first layer, gets an edge from a random
+    /// vertex of the layer above, so every vertex can be reached from the first one.
+    /// Then every other pair of vertices in two consecutive layers gets an edge with
+    /// probability `p`.
+    ///
+    /// The edges are only determined by `seed`, edges added with the randomizing
+    /// functions before are discarded.
+    pub fn build_connected_edges(self, seed: u64, p: f64) -> Vec<(usize, usize)> {
+        let mut lcg = Lcg::with_seed(seed);
+        let mut edges = Vec::new();
+        for layer in 0..self.n.saturating_sub(1) {
+            let (n_upper, upper_start) =
+                self.determine_vertex_indices(self.determine_relative_layer(layer));
+            let (n_lower, lower_start) =
+                self.determine_vertex_indices(self.determine_relative_layer(layer + 1));
+
+            for head in lower_start..lower_start + n_lower {
+                let backbone = upper_start + lcg.generate_range(n_upper);
+                for tail in upper_start..upper_start + n_upper {
+                    if tail == backbone || lcg.next_f64() < p {
+                        edges.push((tail, head));
+                    }
+                }
+            }
+        }
+
+        edges
+    }
+
     /// Add a single random edge between two random layers
     pub fn add_random_edge(mut self) -> Self {
         let layer = self.lcg.generate_range(self.n);
@@ -229,12 +310,12 @@ impl LayeredRandomGraphRandomizer {
     ///
     /// For example in a graph with 4 layers, and degree 3, the indices will be:
     ///
-    ///         0    
+    /// ```text
+    ///         0
     ///     1   2   3
     ///     4   5   6
     ///         7
-    ///
-    ///
+    /// ```
     fn determine_vertex_indices(&self, (layer, is_lower_half): (usize, bool)) -> (usize, usize) {
         // how many vertices are in that layer
         let n_vertices = self.k.pow(layer as u32);
@@ -288,6 +369,70 @@ fn test_layered_graph_randomizer_add_random_edge_even() {
     assert!(actual.0 <= 10 && actual.0 >= 7 && actual.1 <= 12 && actual.1 >= 11);
 }
 
+#[test]
+fn test_layered_graph_try_with_degree() {
+    let edges = LayeredRandomGraph::new(5)
+        .try_with_degree(2)
+        .unwrap()
+        .build();
+    assert_eq!(edges, LayeredRandomGraph::new(5).with_degree(2).build());
+    assert!(LayeredRandomGraph::new(1).try_with_degree(3).is_ok());
+    assert!(matches!(
+        LayeredRandomGraph::new(5).try_with_degree(1),
+        Err(GraphError::InvalidDimension {
+            name: "degree",
+            value: 1
+        })
+    ));
+    for layers in [0, 2] {
+        assert!(matches!(
+            LayeredRandomGraph::new(layers).try_with_degree(2),
+            Err(GraphError::TooFewLayers { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_layered_graph_randomizer_build_with_fan_out() {
+    let fan_out = 2;
+    let lgr = LayeredRandomGraph::new(6).with_seed(42).with_degree(3);
+    let n_vertices = lgr.n_vertices;
+    let edges = lgr.build_with_fan_out(fan_out);
+
+    let mut out_degree = vec![0; n_vertices];
+    edges.iter().for_each(|(tail, _)| out_degree[*tail] += 1);
+    assert!(out_degree.iter().all(|d| *d <= fan_out));
+    assert_eq!(out_degree[0], fan_out);
+
+    // edges are distinct
+    let unique = edges.iter().collect::<std::collections::HashSet<_>>();
+    assert_eq!(unique.len(), edges.len());
+}
+
+#[test]
+fn test_layered_graph_randomizer_build_connected_edges() {
+    use crate::util::connected_components;
+    for (layers, deg) in [(5, 2), (6, 3), (4, 4)] {
+        let lgr = LayeredRandomGraph::new(layers).with_degree(deg);
+        let n_vertices = lgr.n_vertices;
+        let edges = lgr
+            .build_connected_edges(7, 0.)
+            .into_iter()
+            .map(|(t, h)| (t as u32, h as u32))
+            .collect::<Vec<_>>();
+        // only the backbone, a tree on all vertices
+        assert_eq!(edges.len(), n_vertices - 1);
+        assert_eq!(connected_components(&edges, n_vertices).len(), 1);
+    }
+
+    let lgr = LayeredRandomGraph::new(5).with_degree(2);
+    let n_vertices = lgr.n_vertices;
+    let edges = lgr.build_connected_edges(7, 1.);
+    // all edges between consecutive layers: 1*2 + 2*4 + 4*2 + 2*1
+    assert_eq!(edges.len(), 20);
+    assert!(edges.iter().all(|(t, h)| t < h && *h < n_vertices));
+}
+
 #[test]
 fn determine_node_range_2edges_7layers_3() {
     let lgr = LayeredRandomGraph::new(7).with_degree(2);
@@ -1,21 +1,256 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
+
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::fs::File;
-use std::io::Write;
+#[cfg(feature = "std")]
+use std::io::{BufWriter, Write};
+
+#[cfg(feature = "std")]
+use error::GraphError;
 
+#[cfg(test)]
 use comm::CubeGraph;
 
 pub mod comm;
+pub mod error;
+pub mod graph;
+#[cfg(feature = "std")]
+pub mod io;
 pub mod layered;
 pub mod layered_random;
 pub mod random;
+pub mod topology;
+pub mod transform;
 pub mod util;
 
+/// How the weights of weighted edges are formatted when they are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeightFormat {
+    /// A fixed number of digits after the decimal point, like `1.500`.
+    Fixed(usize),
+    /// Scientific notation with a fixed number of digits after the decimal point, like `1.50e0`.
+    Scientific(usize),
+    /// The shortest representation which reads back as the same value, like `1.5`.
+    /// This is the default.
+    #[default]
+    Shortest,
+}
+
+impl WeightFormat {
+    /// Formats `weight` according to this format.
+    pub fn format(&self, weight: f64) -> String {
+        match *self {
+            WeightFormat::Fixed(precision) => format!("{:.*}", precision, weight),
+            WeightFormat::Scientific(precision) => format!("{:.*e}", precision, weight),
+            WeightFormat::Shortest => format!("{}", weight),
+        }
+    }
+}
+
+/// An edge which can be written to a text file as a single line.
+///
+/// Implemented for plain `(tail, head)` edges and weighted `(tail, head, weight)` edges,
+/// where the weight is emitted as a third field.
+pub trait EdgeRecord {
+    /// Formats the edge as a line, without the trailing newline.
+    fn to_line(&self) -> String;
+
+    /// Formats the edge as a line, like [`EdgeRecord::to_line`], with the weight, if there is
+    /// one, formatted according to `format`.
+    fn to_line_with(&self, _format: WeightFormat) -> String {
+        self.to_line()
+    }
+
+    /// The same edge, with `offset` added to the ids of both ends,
+    /// or `None` if an id would overflow.
+    fn offset_ids(&self, offset: u32) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl EdgeRecord for (u32, u32) {
+    fn to_line(&self) -> String {
+        format!("{} -> {}", self.0, self.1)
+    }
+
+    fn offset_ids(&self, offset: u32) -> Option<Self> {
+        Some((self.0.checked_add(offset)?, self.1.checked_add(offset)?))
+    }
+}
+
+impl EdgeRecord for (u32, u32, f64) {
+    fn to_line(&self) -> String {
+        self.to_line_with(WeightFormat::Shortest)
+    }
+
+    fn to_line_with(&self, format: WeightFormat) -> String {
+        format!("{} -> {} {}", self.0, self.1, format.format(self.2))
+    }
+
+    fn offset_ids(&self, offset: u32) -> Option<Self> {
+        Some((self.0.checked_add(offset)?, self.1.checked_add(offset)?, self.2))
+    }
+}
+
 /// Write the edges of a graph to a text file.
-pub fn write_to_file(filename: &str, edges: &[(u32, u32)]) -> std::io::Result<()> {
-    let mut file = File::create(filename)?;
-    
-    let buffer = edges.into_iter().map(|(tail, head)| format!("{} -> {}\n", tail, head)).collect::<String>();
-    file.write_all(buffer.as_bytes())?;
-    Ok(())
+#[cfg(feature = "std")]
+pub fn write_to_file<E: EdgeRecord>(filename: &str, edges: &[E]) -> Result<(), GraphError> {
+    write_to_file_limited(filename, edges, None)
+}
+
+/// Write the edges of a graph to a text file, like [`write_to_file`], but with one-indexed
+/// node ids, as expected by some tools. Every id is increased by one, so an edge with the
+/// id `u32::MAX` results in an error before the file is created.
+#[cfg(feature = "std")]
+pub fn write_to_file_one_indexed<E: EdgeRecord>(
+    filename: &str,
+    edges: &[E],
+) -> Result<(), GraphError> {
+    write_edges(filename, edges, None, 1, WeightFormat::Shortest)
+}
+
+/// Write the edges of a graph to a text file, like [`write_to_file`], with the weights
+/// formatted according to `format`.
+#[cfg(feature = "std")]
+pub fn write_to_file_formatted<E: EdgeRecord>(
+    filename: &str,
+    edges: &[E],
+    format: WeightFormat,
+) -> Result<(), GraphError> {
+    write_edges(filename, edges, None, 0, format)
+}
+
+/// Write the edges of a graph to a text file, like [`write_to_file`].
+///
+/// If there are more than `max_edges` edges, an error is returned before the file is created.
+#[cfg(feature = "std")]
+pub fn write_to_file_limited<E: EdgeRecord>(
+    filename: &str,
+    edges: &[E],
+    max_edges: Option<usize>,
+) -> Result<(), GraphError> {
+    write_edges(filename, edges, max_edges, 0, WeightFormat::Shortest)
+}
+
+#[cfg(feature = "std")]
+fn write_edges<E: EdgeRecord>(
+    filename: &str,
+    edges: &[E],
+    max_edges: Option<usize>,
+    offset: u32,
+    format: WeightFormat,
+) -> Result<(), GraphError> {
+    if let Some(max_edges) = max_edges.filter(|max| edges.len() > *max) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} edges exceed the limit of {} edges", edges.len(), max_edges),
+        )
+        .into());
+    }
+    if edges.iter().any(|edge| edge.offset_ids(offset).is_none()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("an id does not fit into a u32 after adding {}", offset),
+        )
+        .into());
+    }
+
+    let mut file = BufWriter::new(File::create(filename)?);
+    for edge in edges.iter().filter_map(|edge| edge.offset_ids(offset)) {
+        writeln!(file, "{}", edge.to_line_with(format))?;
+    }
+    Ok(file.flush()?)
+}
+
+/// Write the edges of a graph to `writer`, in the same format as [`write_to_file`].
+///
+/// The lines are written in chunks of about 8 KiB, so the edges are never formatted
+/// as a whole before they are written.
+#[cfg(feature = "tokio")]
+pub async fn write_edges_async<W, E>(writer: &mut W, edges: &[E]) -> Result<(), GraphError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    E: EdgeRecord,
+{
+    use tokio::io::AsyncWriteExt;
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    let mut chunk = String::with_capacity(CHUNK_SIZE);
+    for edge in edges {
+        chunk.push_str(&edge.to_line());
+        chunk.push('\n');
+        if chunk.len() >= CHUNK_SIZE {
+            writer.write_all(chunk.as_bytes()).await?;
+            chunk.clear();
+        }
+    }
+    writer.write_all(chunk.as_bytes()).await?;
+    Ok(writer.flush().await?)
+}
+
+/// Read the edges of a graph from a text file written by [`write_to_file`].
+///
+/// Every line has the form `tail -> head`, a trailing weight is ignored.
+/// Empty lines and comment lines starting with `#` are skipped.
+#[cfg(feature = "std")]
+pub fn read_from_file(filename: &str) -> Result<Vec<(u32, u32)>, GraphError> {
+    let content = std::fs::read_to_string(filename)?;
+
+    let mut edges = Vec::new();
+    for line in content.lines() {
+        edges.extend(parse_edge_line(line)?);
+    }
+
+    Ok(edges)
+}
+
+/// Read a file written by [`write_to_file`] line by line and return the number of nodes,
+/// which is one more than the highest id, the number of edges and whether the graph is acyclic.
+///
+/// The lines are not kept in memory, but the cycle check needs all edges,
+/// so they are collected, which takes 8 bytes per edge.
+#[cfg(feature = "std")]
+pub fn validate_file(filename: &str) -> Result<(usize, usize, bool), GraphError> {
+    use std::io::BufRead;
+    let file = std::io::BufReader::new(File::open(filename)?);
+
+    let mut n_nodes = 0;
+    let mut edges = Vec::new();
+    for line in file.lines() {
+        if let Some((tail, head)) = parse_edge_line(&line?)? {
+            n_nodes = n_nodes.max(tail.max(head) as usize + 1);
+            edges.push((tail, head));
+        }
+    }
+
+    Ok((n_nodes, edges.len(), util::is_acyclic(&edges, n_nodes)))
+}
+
+/// Parse a line of the format of [`write_to_file`], returning `None` for empty lines and comments.
+#[cfg(feature = "std")]
+fn parse_edge_line(line: &str) -> std::io::Result<Option<(u32, u32)>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid line '{}'", line),
+        )
+    };
+    let (tail, rest) = line.split_once("->").ok_or_else(invalid)?;
+    let head = rest.split_whitespace().next().ok_or_else(invalid)?;
+    Ok(Some((
+        tail.trim().parse().map_err(|_| invalid())?,
+        head.parse().map_err(|_| invalid())?,
+    )))
 }
 
 #[test]
@@ -25,6 +260,140 @@ fn test_write_to_file() {
     let _ = write_to_file("1000_3", &layout.build_edges());
 }
 
+#[test]
+fn test_write_to_file_weighted() {
+    let dir = std::env::temp_dir();
+    let unweighted = dir.join("graph_generator_unweighted.txt");
+    let weighted = dir.join("graph_generator_weighted.txt");
+
+    write_to_file(unweighted.to_str().unwrap(), &[(0u32, 1u32), (1, 2)]).unwrap();
+    write_to_file(weighted.to_str().unwrap(), &[(0u32, 1u32, 0.5f64), (1, 2, 2.0)]).unwrap();
+
+    assert_eq!(std::fs::read_to_string(unweighted).unwrap(), "0 -> 1\n1 -> 2\n");
+    assert_eq!(std::fs::read_to_string(weighted).unwrap(), "0 -> 1 0.5\n1 -> 2 2\n");
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_write_edges_async() {
+    let filename = std::env::temp_dir().join("graph_generator_async.txt");
+    let filename = filename.to_str().unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    // large enough to be written in several chunks
+    let edges = layered::LayeredGraph::new_from_num_nodes(2000, 3).build_edges();
+    let mut buffer = Vec::new();
+    runtime.block_on(write_edges_async(&mut buffer, &edges)).unwrap();
+    write_to_file(filename, &edges).unwrap();
+    assert!(buffer.len() > 8 * 1024);
+    assert_eq!(buffer, std::fs::read(filename).unwrap());
+
+    let weighted = [(0u32, 1u32, 0.5f64), (1, 2, 2.0)];
+    let mut buffer = Vec::new();
+    runtime.block_on(write_edges_async(&mut buffer, &weighted)).unwrap();
+    assert_eq!(buffer, b"0 -> 1 0.5\n1 -> 2 2\n");
+}
+
+#[test]
+fn test_write_to_file_limited() {
+    let filename = std::env::temp_dir().join("graph_generator_limited.txt");
+    let filename = filename.to_str().unwrap();
+    let _ = std::fs::remove_file(filename);
+    let edges = [(0u32, 1u32), (1, 2), (2, 3)];
+
+    let err = write_to_file_limited(filename, &edges, Some(2)).unwrap_err();
+    assert!(matches!(err, GraphError::Io(err) if err.kind() == std::io::ErrorKind::InvalidInput));
+    assert!(!std::path::Path::new(filename).exists());
+
+    write_to_file_limited(filename, &edges, Some(3)).unwrap();
+    assert_eq!(read_from_file(filename).unwrap(), edges);
+}
+
+#[test]
+fn test_write_to_file_one_indexed() {
+    let dir = std::env::temp_dir();
+    let zero = dir.join("graph_generator_zero_indexed.txt");
+    let one = dir.join("graph_generator_one_indexed.txt");
+    let edges = [(0u32, 1u32), (1, 2), (0, 2)];
+
+    write_to_file(zero.to_str().unwrap(), &edges).unwrap();
+    write_to_file_one_indexed(one.to_str().unwrap(), &edges).unwrap();
+
+    assert_eq!(std::fs::read_to_string(one).unwrap(), "1 -> 2\n2 -> 3\n1 -> 3\n");
+    let zero = read_from_file(zero.to_str().unwrap()).unwrap();
+    assert_eq!(zero, edges);
+
+    let weighted = dir.join("graph_generator_one_indexed_weighted.txt");
+    write_to_file_one_indexed(weighted.to_str().unwrap(), &[(0u32, 4u32, 0.5f64)]).unwrap();
+    assert_eq!(std::fs::read_to_string(weighted).unwrap(), "1 -> 5 0.5\n");
+
+    // the highest id has no one-indexed id, so nothing is written
+    let overflow = dir.join("graph_generator_one_indexed_overflow.txt");
+    let _ = std::fs::remove_file(&overflow);
+    let err = write_to_file_one_indexed(overflow.to_str().unwrap(), &[(0u32, u32::MAX)]);
+    let kind = std::io::ErrorKind::InvalidData;
+    assert!(matches!(err.unwrap_err(), GraphError::Io(err) if err.kind() == kind));
+    assert!(!overflow.exists());
+    assert_eq!((u32::MAX - 1, 0u32, 1.).offset_ids(1), Some((u32::MAX, 1, 1.)));
+}
+
+#[test]
+fn test_weight_format() {
+    assert_eq!(WeightFormat::Scientific(2).format(1.5), "1.50e0");
+    assert_eq!(WeightFormat::Shortest.format(1.5), "1.5");
+    assert_eq!(WeightFormat::Fixed(3).format(1.5), "1.500");
+
+    let filename = std::env::temp_dir().join("graph_generator_formatted.txt");
+    let filename = filename.to_str().unwrap();
+    let edges = [(0u32, 1u32, 0.25f64), (1, 2, 1200.)];
+    write_to_file_formatted(filename, &edges, WeightFormat::Scientific(1)).unwrap();
+    assert_eq!(std::fs::read_to_string(filename).unwrap(), "0 -> 1 2.5e-1\n1 -> 2 1.2e3\n");
+}
+
+#[test]
+fn test_io_graph_error() {
+    fn is_not_found(err: &GraphError) -> bool {
+        matches!(err, GraphError::Io(io) if io.kind() == std::io::ErrorKind::NotFound)
+    }
+    let missing_dir = std::env::temp_dir().join("graph_generator_missing_dir");
+    let _ = std::fs::remove_dir_all(&missing_dir);
+    let filename = missing_dir.join("graph.txt");
+    let filename = filename.to_str().unwrap();
+
+    let err = write_to_file(filename, &[(0u32, 1u32)]).unwrap_err();
+    assert!(is_not_found(&err));
+    assert!(std::error::Error::source(&err).is_some());
+    assert!(is_not_found(&io::write_dot(filename, &[(0, 1)], 2).unwrap_err()));
+    let graph = CubeGraph::new(2, 2, 2, 2);
+    assert!(is_not_found(&graph.write_sectioned(filename).unwrap_err()));
+    assert!(is_not_found(&read_from_file(filename).unwrap_err()));
+}
+
+#[test]
+fn test_validate_file() {
+    let filename = std::env::temp_dir().join("graph_generator_validate.txt");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, "# comment\n0 -> 1\n\n1 -> 4 0.5\n0 -> 4\n").unwrap();
+    assert_eq!(validate_file(filename).unwrap(), (5, 3, true));
+
+    std::fs::write(filename, "0 -> 1\n1 -> 2\n2 -> 0\n").unwrap();
+    assert_eq!(validate_file(filename).unwrap(), (3, 3, false));
+
+    std::fs::write(filename, "0 -> 1\n1 2\n").unwrap();
+    assert!(validate_file(filename).is_err());
+}
+
+#[test]
+fn test_read_from_file() {
+    let filename = std::env::temp_dir().join("graph_generator_read.txt");
+    let filename = filename.to_str().unwrap();
+    std::fs::write(filename, "# comment\n0 -> 1\n\n1 -> 2 0.5\n").unwrap();
+    assert_eq!(read_from_file(filename).unwrap(), vec![(0, 1), (1, 2)]);
+
+    std::fs::write(filename, "0 1\n").unwrap();
+    assert!(read_from_file(filename).is_err());
+}
+
 #[test]
 fn cube_graph_3_dim_2_ts() {
     let layout = CubeGraph::new(3, 3, 3, 2)
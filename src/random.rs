@@ -1,35 +1,477 @@
-use super::util::Lcg;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use super::error::GraphError;
+use super::util::{compact_ids, disjoint_union, is_acyclic, Lcg};
 /// Represents a graph with randomly created edges.
+#[derive(Debug)]
 pub struct RandomGraph {
     num_edges: u32,
+    num_nodes: u32,
+    seed: Option<u64>,
+    mode: Mode,
+    strategy: AcyclicStrategy,
+}
+
+/// How a graph without a depth limit or locality is kept acyclic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AcyclicStrategy {
+    /// Grow the graph from a single edge, removing every edge which closes a cycle again.
+    /// This is the default.
+    RejectCycle,
+    /// Put the nodes in a random order and only add edges from earlier to later nodes.
+    Permutation,
+    /// Assign every node a random level in `0..num_nodes` and only add edges from lower to
+    /// higher levels. Nodes can share a level, so less edges may be possible than with
+    /// [`Permutation`](AcyclicStrategy::Permutation).
+    LevelAssignment,
+}
+
+/// The way edges get created.
+#[derive(Clone, Copy, Debug)]
+enum Mode {
+    /// Grow the graph from a single edge, rejecting edges which close a cycle.
+    Grow,
+    /// Assign every node a random level and only connect lower to higher levels.
+    MaxDepth(usize),
+    /// Like `Grow`, but the successor is drawn close to the predecessor, with spread `sigma`.
+    Locality(f64),
 }
 
 impl RandomGraph {
     pub fn new(num_edges: u32) -> Self {
-        Self { num_edges }
+        Self {
+            num_edges,
+            num_nodes: num_edges.saturating_add(1),
+            seed: None,
+            mode: Mode::Grow,
+            strategy: AcyclicStrategy::RejectCycle,
+        }
+    }
+
+    /// Creates a graph with `num_edges` edges between `num_nodes` nodes.
+    pub fn with_num_nodes(num_nodes: u32, num_edges: u32, seed: u64) -> Self {
+        Self {
+            num_edges,
+            num_nodes,
+            seed: Some(seed),
+            mode: Mode::Grow,
+            strategy: AcyclicStrategy::RejectCycle,
+        }
+    }
+
+    /// Creates a graph like [`with_num_nodes`](RandomGraph::with_num_nodes), but checks that
+    /// an acyclic graph can have `num_edges` edges between `num_nodes` nodes.
+    ///
+    /// Returns [`GraphError::InfeasibleEdgeCount`] otherwise, since creating the edges
+    /// would never finish.
+    pub fn try_with_num_nodes(
+        num_nodes: u32,
+        num_edges: u32,
+        seed: u64,
+    ) -> Result<Self, GraphError> {
+        let graph = Self::with_num_nodes(num_nodes, num_edges, seed);
+        let max = graph.estimate_max_edges();
+        if num_edges as usize > max {
+            return Err(GraphError::InfeasibleEdgeCount {
+                requested: num_edges as usize,
+                max,
+            });
+        }
+        Ok(graph)
+    }
+
+    /// Creates a graph with `num_nodes` nodes and an average degree of `avg_deg`,
+    /// counting every edge once for both of its ends.
+    ///
+    /// The number of edges is `round(avg_deg * num_nodes / 2)`, limited to the maximum
+    /// number of edges of an acyclic graph.
+    pub fn from_avg_degree(num_nodes: u32, avg_deg: f64, seed: u64) -> Self {
+        let max_edges = num_nodes as u64 * num_nodes.saturating_sub(1) as u64 / 2;
+        // `as` saturates negative values to 0, so adding 0.5 rounds
+        let num_edges = ((avg_deg * num_nodes as f64 / 2. + 0.5) as u64).min(max_edges);
+        Self::with_num_nodes(num_nodes, num_edges as u32, seed)
+    }
+
+    /// Creates a graph with `num_edges` edges between `num_nodes` nodes, where the head of a new
+    /// edge is likely to have an id close to the id of its tail.
+    ///
+    /// The distance between the ids is drawn from an approximately normal distribution with
+    /// standard deviation `sigma`, rounded to the nearest nonzero integer.
+    pub fn with_locality(num_nodes: u32, num_edges: u32, sigma: f64, seed: u64) -> Self {
+        Self {
+            num_edges,
+            num_nodes,
+            seed: Some(seed),
+            mode: Mode::Locality(sigma),
+            strategy: AcyclicStrategy::RejectCycle,
+        }
+    }
+
+    /// Creates a graph with `num_nodes` nodes, whose longest path has less than `max_depth` layers.
+    ///
+    /// Every node gets assigned a random level in `0..max_depth` and edges only go from lower
+    /// to higher levels, which makes the graph acyclic without having to check for cycles.
+    /// If there are less possible edges than `num_edges`, all of them are created.
+    pub fn with_max_depth(num_nodes: u32, num_edges: u32, max_depth: usize, seed: u64) -> Self {
+        Self {
+            num_edges,
+            num_nodes,
+            seed: Some(seed),
+            mode: Mode::MaxDepth(max_depth),
+            strategy: AcyclicStrategy::RejectCycle,
+        }
+    }
+
+    /// Set how the graph is kept acyclic. The strategies create differently distributed graphs,
+    /// the ones without cycle checks are faster.
+    ///
+    /// Graphs created with [`with_max_depth`](RandomGraph::with_max_depth) or
+    /// [`with_locality`](RandomGraph::with_locality) are not affected.
+    pub fn with_strategy(mut self, strategy: AcyclicStrategy) -> Self {
+        self.strategy = strategy;
+        self
     }
 
     /// Creates edges of a graph randomly.
     /// The graph created from the edges will be acyclic.
     pub fn build_edges(&self) -> Vec<(u32, u32)> {
-        let mut rng = Lcg::new();
-        let mut edges = vec![(0, 1)];
+        self.build_edges_with_control(|_| {}, || false)
+    }
 
-        while edges.len() < self.num_edges as usize {
-            let current_edge = edges[rng.generate_range(edges.len())];
+    /// Creates edges of a graph randomly, like [`build_edges`](RandomGraph::build_edges).
+    ///
+    /// `on_progress` is called with the current number of edges every time an edge is added.
+    /// If `should_cancel` returns true, the edges created so far are returned.
+    pub fn build_edges_with_control(
+        &self,
+        mut on_progress: impl FnMut(usize),
+        should_cancel: impl Fn() -> bool,
+    ) -> Vec<(u32, u32)> {
+        let (edges, _) =
+            self.build_from_rng(self.rng(), Vec::new(), 0, &mut on_progress, &should_cancel);
+        edges
+    }
 
-            loop {
-                let next_successor = rng.generate_range(self.num_edges as usize + 1) as u32;
-                let next_predecessor = [current_edge.0, current_edge.1][rng.generate_range(2)];
-                if next_successor == next_predecessor {
-                    continue;
+    /// Creates the first `num_edges` edges of [`build_edges`](RandomGraph::build_edges).
+    ///
+    /// Returns the edges together with the number of random values drawn, which can be
+    /// passed to [`resume_from`](RandomGraph::resume_from) to create the remaining edges.
+    pub fn build_partial(&self, num_edges: u32) -> (Vec<(u32, u32)>, u64) {
+        let partial = RandomGraph {
+            num_edges: num_edges.min(self.num_edges),
+            ..*self
+        };
+        partial.build_from_rng(self.rng(), Vec::new(), 0, &mut |_| {}, &|| false)
+    }
+
+    /// Continues creating edges from the `partial` edges returned by
+    /// [`build_partial`](RandomGraph::build_partial), until there are `num_edges` edges.
+    ///
+    /// `draws_consumed` is the number of random values drawn for the partial edges. The result
+    /// is the same as if the edges were created at once.
+    ///
+    /// The random values are drawn from the seed of the graph, like in `build_partial`. `seed`
+    /// is only used to check that the partial edges belong to this graph.
+    ///
+    /// Panics if `seed` is not the seed of the graph. Graphs without a seed can not be resumed,
+    /// since they are seeded from the clock.
+    pub fn resume_from(
+        &self,
+        partial: Vec<(u32, u32)>,
+        seed: u64,
+        draws_consumed: u64,
+    ) -> Vec<(u32, u32)> {
+        assert_eq!(
+            self.seed,
+            Some(seed),
+            "the partial edges were created with a different seed"
+        );
+        let (edges, _) =
+            self.build_from_rng(self.rng(), partial, draws_consumed, &mut |_| {}, &|| false);
+        edges
+    }
+
+    /// Creates a random graph in the `G(n, p)` model, where every pair of the `num_nodes`
+    /// nodes is connected with probability `p`.
+    ///
+    /// The edges go from the lower to the higher id, so the graph is acyclic.
+    pub fn erdos_renyi(num_nodes: u32, p: f64, seed: u64) -> Vec<(u32, u32)> {
+        let mut rng = Lcg::with_seed(seed);
+        let mut edges = Vec::new();
+        for tail in 0..num_nodes {
+            for head in tail + 1..num_nodes {
+                if rng.next_f64() < p {
+                    edges.push((tail, head));
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Creates `num_edges` random edges between `num_nodes` nodes, without checking for cycles.
+    ///
+    /// The nodes are put in a random order and edges only go from earlier to later nodes
+    /// in that order, which makes the graph acyclic. If there are less possible edges than
+    /// `num_edges`, all of them are created.
+    pub fn build_via_permutation(num_nodes: u32, num_edges: u32, seed: u64) -> Vec<(u32, u32)> {
+        let mut rng = Lcg::with_seed(seed);
+        let mut order = (0..num_nodes).collect::<Vec<_>>();
+        rng.shuffle(&mut order);
+
+        let n = num_nodes as usize;
+        let possible_edges = n * n.saturating_sub(1) / 2;
+        let num_edges = (num_edges as usize).min(possible_edges);
+
+        if 2 * num_edges > possible_edges {
+            // dense, rejecting duplicates would take long, so choose from all edges
+            let mut edges = (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .map(|(i, j)| (order[i], order[j]))
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut edges);
+            edges.truncate(num_edges);
+            return edges;
+        }
+
+        let mut edges = Vec::with_capacity(num_edges);
+        let mut seen = BTreeSet::new();
+        while edges.len() < num_edges {
+            let (i, j) = (rng.generate_range(n), rng.generate_range(n));
+            if i < j && seen.insert((i, j)) {
+                edges.push((order[i], order[j]));
+            }
+        }
+
+        edges
+    }
+
+    /// Creates a random graph on `num_nodes` nodes with a planted clique of `clique_size` random
+    /// nodes, which are all connected to each other. Every other pair of nodes is connected with
+    /// probability `background_p`.
+    ///
+    /// Edges go from the lower to the higher id, so the graph is acyclic.
+    /// Returns the edges and the ascending nodes of the clique.
+    pub fn with_planted_clique(
+        num_nodes: u32,
+        clique_size: u32,
+        background_p: f64,
+        seed: u64,
+    ) -> (Vec<(u32, u32)>, Vec<u32>) {
+        let mut rng = Lcg::with_seed(seed);
+        let mut nodes = (0..num_nodes).collect::<Vec<_>>();
+        rng.shuffle(&mut nodes);
+        let mut clique = nodes[..clique_size.min(num_nodes) as usize].to_vec();
+        clique.sort_unstable();
+
+        let mut in_clique = vec![false; num_nodes as usize];
+        clique
+            .iter()
+            .for_each(|node| in_clique[*node as usize] = true);
+        let mut edges = Vec::new();
+        for tail in 0..num_nodes {
+            for head in tail + 1..num_nodes {
+                let planted = in_clique[tail as usize] && in_clique[head as usize];
+                if planted || rng.next_f64() < background_p {
+                    edges.push((tail, head));
                 }
-                let next_edge = (next_predecessor, next_successor);
-                if edges.iter().find(|e| e == &&next_edge).is_none() {
+            }
+        }
+
+        (edges, clique)
+    }
+
+    /// Creates a random layered graph, where layer `l` has `layer_sizes[l]` nodes.
+    /// The layers are numbered consecutively, starting with layer `0`.
+    ///
+    /// Every node of a layer is connected to every node of the next layer with probability `p`,
+    /// so edges only go from one layer to the next one and the graph is acyclic.
+    pub fn layered_random(layer_sizes: &[u32], p: f64, seed: u64) -> Vec<(u32, u32)> {
+        let mut rng = Lcg::with_seed(seed);
+        let mut edges = Vec::new();
+        let mut first_id = 0;
+        for (&size, &next_size) in layer_sizes.iter().zip(layer_sizes.iter().skip(1)) {
+            let next_first_id = first_id + size;
+            for tail in first_id..next_first_id {
+                for head in next_first_id..next_first_id + next_size {
+                    if rng.next_f64() < p {
+                        edges.push((tail, head));
+                    }
+                }
+            }
+            first_id = next_first_id;
+        }
+
+        edges
+    }
+
+    /// Creates edges of a graph randomly, starting from the `initial` edges,
+    /// until there are `num_edges` edges.
+    ///
+    /// Returns an error if the initial edges already contain a cycle.
+    pub fn build_from_seed_edges(
+        &self,
+        initial: Vec<(u32, u32)>,
+    ) -> Result<Vec<(u32, u32)>, GraphError> {
+        if RandomGraph::contains_cycle(&initial) {
+            return Err(GraphError::ContainsCycle);
+        }
+        if initial.is_empty() {
+            return Ok(self.build_edges());
+        }
+
+        Ok(self.grow_edges(initial, &mut self.rng(), &mut |_| {}, &|| false))
+    }
+
+    /// Creates `components` independent random graphs and combines them into one.
+    ///
+    /// The seeds of the components are derived from `seed`. The ids of every component are
+    /// compacted, so nodes without edges are dropped, and offset by the nodes of the previous
+    /// components.
+    ///
+    /// Every component is grown from a single edge like with
+    /// [`RejectCycle`](AcyclicStrategy::RejectCycle), so it stays connected and the result has
+    /// `components` components. The strategy of the graph is ignored. Components of a graph
+    /// with a [max depth](RandomGraph::with_max_depth) are not grown and may fall apart.
+    pub fn build_forest(&self, components: usize, seed: u64) -> Vec<(u32, u32)> {
+        let mut seeds = Lcg::new_seed(seed as u128);
+        let mut edges = Vec::new();
+        let mut n_nodes = 0;
+        for _ in 0..components {
+            let component = RandomGraph {
+                seed: Some(seeds.next_u64()),
+                strategy: AcyclicStrategy::RejectCycle,
+                ..*self
+            };
+            let (component_edges, map) = compact_ids(&component.build_edges());
+            edges = disjoint_union(&edges, n_nodes, &component_edges);
+            n_nodes += map.len();
+        }
+
+        edges
+    }
+
+    /// Calculates an upper bound for the number of edges the graph can have, independent of
+    /// the requested number of edges.
+    ///
+    /// An acyclic graph has at most `n * (n - 1) / 2` edges. If the depth is limited to
+    /// `max_depth` levels, the edges can only go between different levels, which is at most
+    /// the edges of a complete graph with `max_depth` equally sized parts.
+    pub fn estimate_max_edges(&self) -> usize {
+        let n = self.num_nodes as usize;
+        let acyclic = n * n.saturating_sub(1) / 2;
+        match self.mode {
+            Mode::Grow | Mode::Locality(_) if n < 2 => 0,
+            Mode::Grow | Mode::Locality(_) => acyclic,
+            Mode::MaxDepth(max_depth) if max_depth <= 1 => 0,
+            Mode::MaxDepth(max_depth) if max_depth >= n => acyclic,
+            Mode::MaxDepth(max_depth) => {
+                let (size, larger_parts) = (n / max_depth, n % max_depth);
+                let squares = larger_parts * (size + 1) * (size + 1)
+                    + (max_depth - larger_parts) * size * size;
+                (n * n - squares) / 2
+            }
+        }
+    }
+
+    fn rng(&self) -> Lcg {
+        match self.seed {
+            Some(seed) => Lcg::new_seed(seed as u128),
+            None => Lcg::default(),
+        }
+    }
+
+    /// Creates the edges with `rng`, which was just seeded, continuing from the `partial` edges.
+    /// The generator is advanced to `draws_consumed` draws before creating more edges.
+    ///
+    /// Returns the edges and the number of values drawn from `rng`.
+    fn build_from_rng(
+        &self,
+        mut rng: Lcg,
+        partial: Vec<(u32, u32)>,
+        draws_consumed: u64,
+        on_progress: &mut dyn FnMut(usize),
+        should_cancel: &dyn Fn() -> bool,
+    ) -> (Vec<(u32, u32)>, u64) {
+        let edges = match (self.mode, self.strategy) {
+            (Mode::Grow, AcyclicStrategy::Permutation | AcyclicStrategy::LevelAssignment) => {
+                let n = self.num_nodes as usize;
+                // like with `MaxDepth`, the levels are always the first values
+                let levels = if self.strategy == AcyclicStrategy::Permutation {
+                    let mut order = (0..n).collect::<Vec<_>>();
+                    rng.shuffle(&mut order);
+                    let mut levels = vec![0; n];
+                    order
+                        .iter()
+                        .enumerate()
+                        .for_each(|(i, node)| levels[*node] = i);
+                    levels
+                } else {
+                    (0..n).map(|_| rng.generate_range(n)).collect()
+                };
+                rng.jump(draws_consumed.saturating_sub(rng.draws()));
+                self.build_leveled_edges(n, &levels, partial, &mut rng, on_progress, should_cancel)
+            }
+            (Mode::Grow | Mode::Locality(_), _) => {
+                if partial.is_empty() && (self.num_edges == 0 || self.num_nodes < 2) {
+                    return (Vec::new(), rng.draws());
+                }
+                let edges = if partial.is_empty() {
+                    vec![(0, 1)]
+                } else {
+                    partial
+                };
+                rng.jump(draws_consumed.saturating_sub(rng.draws()));
+                self.grow_edges(edges, &mut rng, on_progress, should_cancel)
+            }
+            (Mode::MaxDepth(max_depth), _) => {
+                if max_depth <= 1 {
+                    return (Vec::new(), rng.draws());
+                }
+                // the levels are always the first values, so they are the same when resuming
+                let levels = (0..self.num_nodes)
+                    .map(|_| rng.generate_range(max_depth))
+                    .collect::<Vec<_>>();
+                rng.jump(draws_consumed.saturating_sub(rng.draws()));
+                self.build_leveled_edges(
+                    max_depth,
+                    &levels,
+                    partial,
+                    &mut rng,
+                    on_progress,
+                    should_cancel,
+                )
+            }
+        };
+
+        (edges, rng.draws())
+    }
+
+    /// Adds random edges adjacent to the existing ones, until there are `num_edges` edges.
+    fn grow_edges(
+        &self,
+        mut edges: Vec<(u32, u32)>,
+        rng: &mut Lcg,
+        on_progress: &mut dyn FnMut(usize),
+        should_cancel: &dyn Fn() -> bool,
+    ) -> Vec<(u32, u32)> {
+        while edges.len() < self.num_edges as usize && !should_cancel() {
+            let current_edge = edges[rng.generate_range(edges.len())];
+
+            // the ends of the current edge might already be connected to every possible
+            // successor, so only try a limited number of times before choosing another edge
+            for _ in 0..100 {
+                let next_edge = match self.next_edge(rng, current_edge) {
+                    Some(edge) => edge,
+                    None => continue,
+                };
+                if !edges.contains(&next_edge) {
                     edges.push(next_edge);
                     if RandomGraph::contains_cycle(&edges) {
                         edges.pop();
                     } else {
+                        on_progress(edges.len());
                         break;
                     }
                 }
@@ -39,32 +481,160 @@ impl RandomGraph {
         edges
     }
 
+    /// Draws a new edge, whose tail is one of the ends of `current_edge`.
+    /// Returns `None` if the drawn edge is a loop or its head is not a node of the graph.
+    fn next_edge(&self, rng: &mut Lcg, current_edge: (u32, u32)) -> Option<(u32, u32)> {
+        let (next_predecessor, next_successor) = match self.mode {
+            Mode::Locality(sigma) => {
+                let next_predecessor = [current_edge.0, current_edge.1][rng.generate_range(2)];
+                // the sum of 12 uniform values has mean 6 and variance 1
+                let x = sigma * ((0..12).map(|_| rng.next_f64()).sum::<f64>() - 6.);
+                let offset = if x >= 0. {
+                    ((x + 0.5) as i64).max(1)
+                } else {
+                    ((x - 0.5) as i64).min(-1)
+                };
+                let next_successor = next_predecessor as i64 + offset;
+                if !(0..self.num_nodes as i64).contains(&next_successor) {
+                    return None;
+                }
+                (next_predecessor, next_successor as u32)
+            }
+            _ => {
+                let next_successor = rng.generate_range(self.num_nodes as usize) as u32;
+                let next_predecessor = [current_edge.0, current_edge.1][rng.generate_range(2)];
+                (next_predecessor, next_successor)
+            }
+        };
+
+        (next_successor != next_predecessor).then_some((next_predecessor, next_successor))
+    }
+
+    /// Adds random edges from lower to higher levels, until there are `num_edges` edges.
+    fn build_leveled_edges(
+        &self,
+        max_depth: usize,
+        levels: &[usize],
+        mut edges: Vec<(u32, u32)>,
+        rng: &mut Lcg,
+        on_progress: &mut dyn FnMut(usize),
+        should_cancel: &dyn Fn() -> bool,
+    ) -> Vec<(u32, u32)> {
+        // count how many edges are possible at all, so we don't try forever
+        let mut level_sizes = vec![0usize; max_depth];
+        levels.iter().for_each(|l| level_sizes[*l] += 1);
+        let mut possible_edges = 0;
+        let mut lower_nodes = 0;
+        for size in level_sizes {
+            possible_edges += lower_nodes * size;
+            lower_nodes += size;
+        }
+        let num_edges = (self.num_edges as usize).min(possible_edges);
+
+        let mut seen = edges.iter().copied().collect::<BTreeSet<_>>();
+        while edges.len() < num_edges && !should_cancel() {
+            let tail = rng.generate_range(self.num_nodes as usize) as u32;
+            let head = rng.generate_range(self.num_nodes as usize) as u32;
+            if levels[tail as usize] < levels[head as usize] && seen.insert((tail, head)) {
+                edges.push((tail, head));
+                on_progress(edges.len());
+            }
+        }
+
+        edges
+    }
+
     /// Checks if the edges of the graph contain a cycle.
     fn contains_cycle(edges: &[(u32, u32)]) -> bool {
-        let mut visited = std::collections::HashSet::new();
-        for edge in edges {
-            if visited.contains(edge) {
-                continue;
-            }
-            let mut frontier = vec![edge];
-            while let Some(edge) = frontier.pop() {
-                for successor in edges.iter().filter(|(p, _)| edge.1 == *p) {
-                    if visited.contains(successor) {
-                        return true;
-                    }
-                    frontier.push(successor);
+        let n_nodes = edges
+            .iter()
+            .map(|(tail, head)| *tail.max(head) as usize + 1)
+            .max()
+            .unwrap_or(0);
+        !is_acyclic(edges, n_nodes)
+    }
+}
+
+/// Draws graphs with at most 32 nodes and at most as many edges as an acyclic graph can have,
+/// so creating the edges always finishes. Graphs with locality get less edges than nodes.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for RandomGraph {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let num_nodes = u.int_in_range(0..=32)?;
+        let seed = u.arbitrary()?;
+        let mode = match u.int_in_range(0..=2)? {
+            0 => Mode::Grow,
+            1 => Mode::MaxDepth(u.int_in_range(0..=8)?),
+            _ => Mode::Locality(u.int_in_range(1..=64)? as f64 / 4.),
+        };
+        let strategy = *u.choose(&[
+            AcyclicStrategy::RejectCycle,
+            AcyclicStrategy::Permutation,
+            AcyclicStrategy::LevelAssignment,
+        ])?;
+        let mut graph = Self {
+            num_edges: 0,
+            num_nodes,
+            seed: Some(seed),
+            mode,
+            strategy,
+        };
+        let max_edges = match mode {
+            // distant nodes are hardly ever connected, so dense graphs would take too long
+            Mode::Locality(_) => num_nodes.saturating_sub(1),
+            _ => graph.estimate_max_edges() as u32,
+        };
+        graph.num_edges = u.int_in_range(0..=max_edges)?;
+
+        Ok(graph)
+    }
+}
+
+/// A random geometric graph, whose `n` nodes are points placed uniformly in the unit square.
+/// Two nodes are connected if their distance is at most `r`.
+pub struct RandomGeometric {
+    n: u32,
+    r: f64,
+    points: Vec<(f64, f64)>,
+}
+
+impl RandomGeometric {
+    /// Places `n` points, which only depend on `seed`.
+    pub fn new(n: u32, r: f64, seed: u64) -> Self {
+        let mut rng = Lcg::with_seed(seed);
+        let points = (0..n).map(|_| (rng.next_f64(), rng.next_f64())).collect();
+        Self { n, r, points }
+    }
+
+    /// The position `(x, y)` of every node.
+    pub fn coords(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Creates the edges between all nodes within distance `r` of each other.
+    /// Every edge goes from the lower to the higher id, so the graph is acyclic.
+    pub fn build_edges(&self) -> Vec<(u32, u32)> {
+        let mut edges = Vec::new();
+        for tail in 0..self.n {
+            let (x, y) = self.points[tail as usize];
+            for head in tail + 1..self.n {
+                let (other_x, other_y) = self.points[head as usize];
+                let (dx, dy) = (x - other_x, y - other_y);
+                if dx * dx + dy * dy <= self.r * self.r {
+                    edges.push((tail, head));
                 }
-                visited.insert(edge);
             }
         }
 
-        false
+        edges
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RandomGraph;
+    use super::{AcyclicStrategy, RandomGeometric, RandomGraph};
+    use crate::error::GraphError;
+    use crate::util::{compact_ids, connected_components, longest_path_layers};
 
     #[test]
     fn test_random_layout() {
@@ -73,6 +643,300 @@ mod tests {
         println!("{:?}", edges);
     }
 
+    #[test]
+    fn test_with_max_depth() {
+        let max_depth = 4;
+        let edges = RandomGraph::with_max_depth(50, 200, max_depth, 42).build_edges();
+        assert_eq!(edges.len(), 200);
+
+        let layers = longest_path_layers(&edges, 50).unwrap();
+        assert!(*layers.iter().max().unwrap() < max_depth);
+    }
+
+    #[test]
+    fn test_build_from_seed_edges() {
+        let initial = vec![(0, 1), (1, 2)];
+        let edges = RandomGraph::new(10).build_from_seed_edges(initial).unwrap();
+        assert_eq!(edges.len(), 10);
+        assert_eq!(&edges[..2], &[(0, 1), (1, 2)]);
+        assert!(!RandomGraph::contains_cycle(&edges));
+
+        let cyclic = vec![(0, 1), (1, 0)];
+        assert!(matches!(
+            RandomGraph::new(10).build_from_seed_edges(cyclic),
+            Err(GraphError::ContainsCycle)
+        ));
+    }
+
+    #[test]
+    fn test_build_forest() {
+        let components = 4;
+        let edges = RandomGraph::new(8).build_forest(components, 7);
+        assert_eq!(edges.len(), components * 8);
+
+        let n_nodes = compact_ids(&edges).1.len();
+        assert_eq!(connected_components(&edges, n_nodes).len(), components);
+        assert_eq!(edges, RandomGraph::new(8).build_forest(components, 7));
+
+        for strategy in [
+            AcyclicStrategy::RejectCycle,
+            AcyclicStrategy::Permutation,
+            AcyclicStrategy::LevelAssignment,
+        ] {
+            let graph = RandomGraph::with_num_nodes(20, 6, 1).with_strategy(strategy);
+            let edges = graph.build_forest(3, 7);
+            assert_eq!(edges.len(), 18);
+            let n_nodes = compact_ids(&edges).1.len();
+            assert_eq!(connected_components(&edges, n_nodes).len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_from_avg_degree() {
+        let (num_nodes, avg_deg) = (40, 3.3);
+        let edges = RandomGraph::from_avg_degree(num_nodes, avg_deg, 5).build_edges();
+        let actual = 2. * edges.len() as f64 / num_nodes as f64;
+        assert!((actual - avg_deg).abs() <= 1. / num_nodes as f64);
+        assert!(edges.iter().all(|(t, h)| *t < num_nodes && *h < num_nodes));
+
+        // limited to the maximum number of edges
+        let edges = RandomGraph::from_avg_degree(5, 100., 5).build_edges();
+        assert_eq!(edges.len(), 10);
+        assert!(!RandomGraph::contains_cycle(&edges));
+    }
+
+    #[test]
+    fn test_erdos_renyi() {
+        let edges = RandomGraph::erdos_renyi(40, 0.25, 2);
+        assert!(edges.iter().all(|(t, h)| t < h && *h < 40));
+        // 195 edges are expected
+        assert!((150..250).contains(&edges.len()));
+        assert_eq!(edges, RandomGraph::erdos_renyi(40, 0.25, 2));
+
+        assert_eq!(RandomGraph::erdos_renyi(10, 1., 2).len(), 45);
+        assert!(RandomGraph::erdos_renyi(10, 0., 2).is_empty());
+        assert!(RandomGraph::erdos_renyi(0, 1., 2).is_empty());
+    }
+
+    #[test]
+    fn test_with_locality() {
+        let mean_length = |edges: &[(u32, u32)]| {
+            let total = edges
+                .iter()
+                .map(|(t, h)| t.abs_diff(*h) as f64)
+                .sum::<f64>();
+            total / edges.len() as f64
+        };
+        let local = RandomGraph::with_locality(200, 300, 2., 8).build_edges();
+        let uniform = RandomGraph::with_num_nodes(200, 300, 8).build_edges();
+        assert_eq!(local.len(), 300);
+        assert!(!RandomGraph::contains_cycle(&local));
+        assert!(mean_length(&local) < mean_length(&uniform) / 4.);
+    }
+
+    #[test]
+    fn test_estimate_max_edges() {
+        let graphs = [
+            RandomGraph::with_num_nodes(10, 30, 2),
+            RandomGraph::with_max_depth(30, 1000, 3, 2),
+            RandomGraph::with_max_depth(5, 100, 10, 2),
+            RandomGraph::with_locality(10, 20, 1., 2),
+        ];
+        for graph in graphs {
+            let n = graph.num_nodes as usize;
+            let estimate = graph.estimate_max_edges();
+            assert!(graph.build_edges().len() <= estimate);
+            assert!(estimate <= n * (n - 1) / 2);
+        }
+        // three levels of 10 nodes
+        assert_eq!(
+            RandomGraph::with_max_depth(30, 1000, 3, 2).estimate_max_edges(),
+            300
+        );
+        assert_eq!(
+            RandomGraph::with_max_depth(30, 1000, 1, 2).estimate_max_edges(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_build_via_permutation() {
+        for (num_nodes, num_edges) in [(100, 300), (10, 40), (10, 100)] {
+            let edges = RandomGraph::build_via_permutation(num_nodes, num_edges, 6);
+            let max_edges = num_nodes * (num_nodes - 1) / 2;
+            assert_eq!(edges.len() as u32, num_edges.min(max_edges));
+            assert!(!RandomGraph::contains_cycle(&edges));
+            assert_eq!(crate::util::dedup_edges(&edges).len(), edges.len());
+            assert!(edges.iter().all(|(t, h)| *t < num_nodes && *h < num_nodes));
+        }
+        assert_eq!(
+            RandomGraph::build_via_permutation(50, 80, 2),
+            RandomGraph::build_via_permutation(50, 80, 2)
+        );
+    }
+
+    #[test]
+    fn test_with_planted_clique() {
+        let (edges, clique) = RandomGraph::with_planted_clique(30, 6, 0.1, 9);
+        assert_eq!(clique.len(), 6);
+        for (i, tail) in clique.iter().enumerate() {
+            for head in &clique[i + 1..] {
+                assert!(edges.contains(&(*tail, *head)));
+            }
+        }
+        assert!(!RandomGraph::contains_cycle(&edges));
+        // most of the other pairs are not connected
+        assert!(edges.len() < 15 + 435 / 4);
+        assert_eq!(
+            (edges, clique),
+            RandomGraph::with_planted_clique(30, 6, 0.1, 9)
+        );
+
+        let (edges, clique) = RandomGraph::with_planted_clique(5, 9, 0., 9);
+        assert_eq!((edges.len(), clique), (10, vec![0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_random_geometric() {
+        let graph = RandomGeometric::new(80, 0.15, 6);
+        let edges = graph.build_edges();
+        assert_eq!(graph.coords().len(), 80);
+        for (tail, head) in &edges {
+            let (x, y) = graph.coords()[*tail as usize];
+            let (other_x, other_y) = graph.coords()[*head as usize];
+            assert!((x - other_x).hypot(y - other_y) <= 0.15);
+        }
+        assert!(graph
+            .coords()
+            .iter()
+            .all(|(x, y)| (0. ..1.).contains(x) && (0. ..1.).contains(y)));
+
+        let denser = RandomGeometric::new(80, 0.3, 6).build_edges();
+        assert!(denser.len() > edges.len());
+        assert!(edges.iter().all(|edge| denser.contains(edge)));
+        assert_eq!(edges, RandomGeometric::new(80, 0.15, 6).build_edges());
+        assert_eq!(RandomGeometric::new(10, 1.5, 6).build_edges().len(), 45);
+    }
+
+    #[test]
+    fn test_layered_random() {
+        let layer_sizes = [3, 5, 1, 4];
+        let layer_of = |node: u32| match node {
+            0..=2 => 0,
+            3..=7 => 1,
+            8 => 2,
+            _ => 3,
+        };
+        let edges = RandomGraph::layered_random(&layer_sizes, 0.5, 4);
+        assert!(!edges.is_empty());
+        assert!(edges
+            .iter()
+            .all(|(t, h)| layer_of(*h) == layer_of(*t) + 1 && *h < 13));
+        assert_eq!(edges, RandomGraph::layered_random(&layer_sizes, 0.5, 4));
+
+        assert_eq!(
+            RandomGraph::layered_random(&layer_sizes, 1., 4).len(),
+            3 * 5 + 5 + 4
+        );
+        assert!(RandomGraph::layered_random(&layer_sizes, 0., 4).is_empty());
+    }
+
+    #[test]
+    fn test_try_with_num_nodes() {
+        let graph = RandomGraph::try_with_num_nodes(10, 45, 3).unwrap();
+        assert_eq!(graph.build_edges().len(), 45);
+        assert!(matches!(
+            RandomGraph::try_with_num_nodes(10, 46, 3),
+            Err(GraphError::InfeasibleEdgeCount {
+                requested: 46,
+                max: 45
+            })
+        ));
+        assert!(RandomGraph::try_with_num_nodes(1, 1, 3).is_err());
+    }
+
+    #[test]
+    fn test_with_strategy() {
+        let strategies = [
+            AcyclicStrategy::RejectCycle,
+            AcyclicStrategy::Permutation,
+            AcyclicStrategy::LevelAssignment,
+        ];
+        for strategy in strategies {
+            let graph = RandomGraph::with_num_nodes(60, 120, 8).with_strategy(strategy);
+            let edges = graph.build_edges();
+            assert_eq!(edges.len(), 120);
+            assert!(!RandomGraph::contains_cycle(&edges));
+            assert_eq!(crate::util::dedup_edges(&edges).len(), edges.len());
+            assert!(edges.iter().all(|(t, h)| *t < 60 && *h < 60));
+        }
+
+        // every possible edge
+        let graph =
+            RandomGraph::with_num_nodes(8, 28, 8).with_strategy(AcyclicStrategy::Permutation);
+        assert_eq!(graph.build_edges().len(), 28);
+    }
+
+    #[test]
+    fn test_resume_from() {
+        let graphs = [
+            RandomGraph::with_num_nodes(40, 50, 12),
+            RandomGraph::with_max_depth(40, 50, 5, 12),
+            RandomGraph::with_locality(40, 50, 3., 12),
+            RandomGraph::with_num_nodes(40, 50, 12).with_strategy(AcyclicStrategy::Permutation),
+            RandomGraph::with_num_nodes(40, 50, 12).with_strategy(AcyclicStrategy::LevelAssignment),
+        ];
+        for graph in graphs {
+            let straight = graph.build_edges();
+            let (partial, draws) = graph.build_partial(25);
+            assert_eq!(partial[..], straight[..25]);
+            assert_eq!(graph.resume_from(partial, 12, draws), straight);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "different seed")]
+    fn test_resume_from_other_seed() {
+        let graph = RandomGraph::with_num_nodes(40, 50, 12);
+        let (partial, draws) = graph.build_partial(25);
+        graph.resume_from(partial, 13, draws);
+    }
+
+    #[test]
+    fn test_build_edges_with_control() {
+        use std::cell::Cell;
+        let graph = RandomGraph::with_num_nodes(30, 60, 1);
+        let current = Cell::new(0);
+        let edges = graph.build_edges_with_control(|n| current.set(n), || current.get() >= 20);
+        assert_eq!(edges.len(), 20);
+
+        let mut reported = Vec::new();
+        let edges = graph.build_edges_with_control(|n| reported.push(n), || false);
+        assert_eq!(edges.len(), 60);
+        assert_eq!(reported, (2..=60).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use crate::util::{is_acyclic, Lcg};
+
+        let mut rng = Lcg::with_seed(7);
+        let mut data = [0; 32];
+        for _ in 0..500 {
+            rng.fill_bytes(&mut data);
+            let mut u = arbitrary::Unstructured::new(&data);
+            let graph = <RandomGraph as arbitrary::Arbitrary>::arbitrary(&mut u).unwrap();
+            assert!(graph.num_nodes <= 32);
+            assert!(graph.num_edges as usize <= graph.estimate_max_edges());
+
+            let edges = graph.build_edges();
+            assert!(edges.len() <= graph.num_edges as usize);
+            assert!(edges.iter().all(|(t, h)| *t.max(h) < graph.num_nodes));
+            assert!(is_acyclic(&edges, graph.num_nodes as usize));
+        }
+    }
+
     #[test]
     fn test_contains_cycle() {
         assert!(RandomGraph::contains_cycle(&[
@@ -82,5 +946,13 @@ mod tests {
             (2, 5),
             (5, 0)
         ]));
+        // paths joining again are no cycle
+        assert!(!RandomGraph::contains_cycle(&[
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4)
+        ]));
     }
 }
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::error::GraphError;
+use crate::util::Lcg;
+
+/// Creates the binary hypercube Q_n, with nodes `0..2^n`.
+/// Two nodes are connected if their ids differ in exactly one bit.
+///
+/// Every edge is only added once, going from the lower to the higher id,
+/// so there are `n * 2^(n - 1)` edges, which are already more than 10 million for `n = 20`.
+///
+/// Panics if `n` is 32 or more, since the ids of the nodes would not fit into a `u32`.
+/// Use [`try_hypercube`] to get an error instead.
+pub fn hypercube(n: u32) -> Vec<(u32, u32)> {
+    try_hypercube(n).unwrap_or_else(|_| panic!("a hypercube of dimension {} has too many nodes", n))
+}
+
+/// Creates the binary hypercube Q_n like [`hypercube`], but returns
+/// [`GraphError::Overflow`] if `n` is 32 or more, instead of panicking.
+pub fn try_hypercube(n: u32) -> Result<Vec<(u32, u32)>, GraphError> {
+    let n_nodes = 1u32.checked_shl(n).ok_or(GraphError::Overflow)?;
+    Ok((0..n_nodes)
+        .flat_map(|node| {
+            (0..n)
+                .map(move |bit| node ^ (1 << bit))
+                .filter(move |neighbor| *neighbor > node)
+                .map(move |neighbor| (node, neighbor))
+        })
+        .collect())
+}
+
+/// Creates a cycle `0 -> 1 -> ... -> n - 1 -> 0`.
+/// Unlike the other generators, the result is intentionally cyclic.
+///
+/// The smallest cycles are degenerate: `n = 0` gives no edges, `n = 1` the loop `(0, 0)`
+/// and `n = 2` the two opposite edges `(0, 1)` and `(1, 0)`.
+pub fn cycle_graph(n: u32) -> Vec<(u32, u32)> {
+    (0..n).map(|node| (node, (node + 1) % n)).collect()
+}
+
+/// Creates a path `0 -> 1 -> ... -> n - 1`.
+pub fn path_graph(n: u32) -> Vec<(u32, u32)> {
+    (1..n).map(|node| (node - 1, node)).collect()
+}
+
+/// Creates a star with center `0`, which has an edge to every other of the `n` nodes.
+pub fn star_graph(n: u32) -> Vec<(u32, u32)> {
+    (1..n).map(|node| (0, node)).collect()
+}
+
+/// Creates a simple graph in which node `i` has degree `degrees[i]`, using the Havel-Hakimi
+/// algorithm. Nodes of equal degree are connected in a pseudo random order, which only
+/// depends on `seed`.
+///
+/// Every edge goes from the lower to the higher id.
+/// Returns `None` if there is no simple graph with these degrees.
+pub fn from_degree_sequence(degrees: &[u32], seed: u64) -> Option<Vec<(u32, u32)>> {
+    let mut remaining = degrees
+        .iter()
+        .enumerate()
+        .map(|(node, degree)| (*degree, node as u32))
+        .collect::<Vec<_>>();
+    Lcg::with_seed(seed).shuffle(&mut remaining);
+
+    let mut edges = Vec::new();
+    while !remaining.is_empty() {
+        // the sort is stable, so ties keep the shuffled order
+        remaining.sort_by_key(|(degree, _)| Reverse(*degree));
+        let (degree, node) = remaining.remove(0);
+        if degree as usize > remaining.len() {
+            return None;
+        }
+        for (neighbor_degree, neighbor) in remaining.iter_mut().take(degree as usize) {
+            if *neighbor_degree == 0 {
+                return None;
+            }
+            *neighbor_degree -= 1;
+            edges.push((node.min(*neighbor), node.max(*neighbor)));
+        }
+    }
+
+    Some(edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{is_acyclic, out_degrees};
+
+    #[test]
+    fn test_hypercube() {
+        let edges = hypercube(3);
+        assert_eq!(edges.len(), 12);
+
+        let mut degree = [0; 8];
+        for (tail, head) in &edges {
+            assert_eq!((tail ^ head).count_ones(), 1);
+            degree[*tail as usize] += 1;
+            degree[*head as usize] += 1;
+        }
+        assert!(degree.iter().all(|d| *d == 3));
+        assert!(hypercube(0).is_empty());
+        assert_eq!(hypercube(1), vec![(0, 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many nodes")]
+    fn test_hypercube_too_large() {
+        hypercube(32);
+    }
+
+    #[test]
+    fn test_try_hypercube() {
+        assert_eq!(try_hypercube(3).unwrap(), hypercube(3));
+        assert!(matches!(try_hypercube(32), Err(GraphError::Overflow)));
+        assert!(matches!(try_hypercube(40), Err(GraphError::Overflow)));
+    }
+
+    #[test]
+    fn test_cycle_graph() {
+        assert!(!is_acyclic(&cycle_graph(5), 5));
+        assert_eq!(cycle_graph(3), vec![(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(cycle_graph(0), vec![]);
+        assert_eq!(cycle_graph(1), vec![(0, 0)]);
+        assert_eq!(cycle_graph(2), vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn test_from_degree_sequence() {
+        let mut triangle = from_degree_sequence(&[2, 2, 2], 3).unwrap();
+        triangle.sort();
+        assert_eq!(triangle, vec![(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(from_degree_sequence(&[1, 0], 3), None);
+        assert_eq!(from_degree_sequence(&[3, 3, 1, 1], 3), None);
+
+        let degrees = [3, 3, 2, 2, 2, 1, 1];
+        let edges = from_degree_sequence(&degrees, 7).unwrap();
+        let mut count = [0; 7];
+        for (tail, head) in &edges {
+            assert!(tail < head);
+            count[*tail as usize] += 1;
+            count[*head as usize] += 1;
+        }
+        assert_eq!(count, degrees);
+        assert_eq!(crate::util::dedup_edges(&edges).len(), edges.len());
+        assert_eq!(Some(edges), from_degree_sequence(&degrees, 7));
+    }
+
+    #[test]
+    fn test_path_graph() {
+        assert_eq!(path_graph(4), vec![(0, 1), (1, 2), (2, 3)]);
+        assert!(path_graph(1).is_empty());
+        assert!(path_graph(0).is_empty());
+    }
+
+    #[test]
+    fn test_star_graph() {
+        let n = 6;
+        let degrees = out_degrees(&star_graph(n), n as usize);
+        assert_eq!(degrees[0], n as usize - 1);
+        assert!(degrees[1..].iter().all(|d| *d == 0));
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use crate::util::{compact_ids, dedup_edges};
+
+/// A transformation of the edges of a graph.
+///
+/// Implemented for closures taking and returning the edges, so ad hoc steps,
+/// like filters, can be used in a [`Chain`] as well.
+pub trait EdgeTransform {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)>;
+}
+
+impl<F: Fn(Vec<(u32, u32)>) -> Vec<(u32, u32)>> EdgeTransform for F {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        self(edges)
+    }
+}
+
+/// Removes duplicate edges, see [`dedup_edges`].
+pub struct Dedup;
+
+impl EdgeTransform for Dedup {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        dedup_edges(&edges)
+    }
+}
+
+/// Turns every edge into the unordered pair `(min, max)` of its ends, keeping the order
+/// of the edges. Edges in both directions become parallel, which [`Dedup`] removes.
+pub struct Undirected;
+
+impl EdgeTransform for Undirected {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        edges
+            .into_iter()
+            .map(|(tail, head)| (tail.min(head), tail.max(head)))
+            .collect()
+    }
+}
+
+/// Relabels the nodes with contiguous ids, see [`compact_ids`]. The map of ids is discarded.
+pub struct Compact;
+
+impl EdgeTransform for Compact {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        compact_ids(&edges).0
+    }
+}
+
+/// Keeps only the edges between the given nodes.
+pub struct InducedSubgraph {
+    nodes: BTreeSet<u32>,
+}
+
+impl InducedSubgraph {
+    pub fn new(nodes: &[u32]) -> Self {
+        Self {
+            nodes: nodes.iter().copied().collect(),
+        }
+    }
+}
+
+impl EdgeTransform for InducedSubgraph {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        edges
+            .into_iter()
+            .filter(|(tail, head)| self.nodes.contains(tail) && self.nodes.contains(head))
+            .collect()
+    }
+}
+
+/// Applies a sequence of transformations, in the order they were added.
+/// An empty chain returns the edges unchanged.
+#[derive(Default)]
+pub struct Chain {
+    steps: Vec<Box<dyn EdgeTransform>>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `step` to the end of the chain.
+    pub fn then<T: EdgeTransform + 'static>(mut self, step: T) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+}
+
+impl EdgeTransform for Chain {
+    fn apply(&self, edges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+        self.steps
+            .iter()
+            .fold(edges, |edges, step| step.apply(edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        let edges = vec![(5, 2), (2, 5), (2, 9), (5, 2)];
+        let chain = Chain::new().then(Undirected).then(Dedup);
+        assert_eq!(
+            chain.apply(edges.clone()),
+            Dedup.apply(Undirected.apply(edges.clone()))
+        );
+        assert_eq!(chain.apply(edges.clone()), vec![(2, 5), (2, 9)]);
+
+        let chain = chain
+            .then(InducedSubgraph::new(&[2, 9]))
+            .then(|edges: Vec<(u32, u32)>| edges.into_iter().filter(|(t, _)| *t == 2).collect())
+            .then(Compact);
+        assert_eq!(chain.apply(edges.clone()), vec![(0, 1)]);
+        assert_eq!(Chain::new().apply(edges.clone()), edges);
+    }
+}
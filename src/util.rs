@@ -1,46 +1,145 @@
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
 use std::time::SystemTime;
 
-const LCG_MULTIPLIER: usize = 0x5deece66d;
-const LCG_INCREMENT: usize = 0x5deece66d;
-
-pub(crate) struct Lcg {
+/// A linear congruential generator, used as source of randomness for the generators.
+///
+/// The state is advanced by `state = state * MULTIPLIER - INCREMENT mod 2^MODULUS_BITS`,
+/// the output is the upper 64 bits of the state.
+pub struct Lcg {
     state: u128,
-    a: usize,
-    c: usize,
+    draws: u64,
 }
 
 impl Lcg {
+    pub const MULTIPLIER: u128 = 0x5deece66d;
+    pub const INCREMENT: u128 = 0x5deece66d;
+    /// The modulus is `2^MODULUS_BITS`, which does not fit in a `u128`.
+    pub const MODULUS_BITS: u32 = 128;
+
+    #[cfg(feature = "std")]
     pub fn new() -> Self {
         // generater state from clock
         let state = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
             .as_nanos();
-        Self {
-            state,
-            a: LCG_MULTIPLIER,
-            c: LCG_INCREMENT,
-        }
+        Self { state, draws: 0 }
     }
 
     pub fn new_seed(seed: u128) -> Self {
         Self {
             state: seed,
-            a: LCG_MULTIPLIER,
-            c: LCG_INCREMENT,
+            draws: 0,
+        }
+    }
+
+    /// Creates a generator from a 64 bit seed, the same as `new_seed(seed as u128)`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new_seed(seed as u128)
+    }
+
+    /// Creates a generator from a 64 bit seed, like [`with_seed`](Lcg::with_seed),
+    /// and discards the first `rounds` outputs, see [`warm_up`](Lcg::warm_up).
+    pub fn with_seed_warmed(seed: u64, rounds: usize) -> Self {
+        let mut lcg = Self::with_seed(seed);
+        lcg.warm_up(rounds);
+        lcg
+    }
+
+    /// Discards the next `rounds` outputs, which are counted as [`draws`](Lcg::draws).
+    ///
+    /// With a small seed only the low bits of the state are set, so the first few outputs,
+    /// which are taken from the high bits, are close to `0`. Each step fills about 34 more
+    /// bits, so discarding 8 outputs after seeding is recommended for small seeds.
+    /// The generators of the graphs don't warm up, to keep their results unchanged.
+    pub fn warm_up(&mut self, rounds: usize) {
+        self.jump(rounds as u64);
+    }
+
+    /// Resets the generator to the state of [`with_seed(seed)`](Lcg::with_seed),
+    /// which also resets the number of [`draws`](Lcg::draws).
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Self::with_seed(seed);
+    }
+
+    /// The number of outputs drawn since the generator was created,
+    /// including the ones skipped with [`jump`](Lcg::jump).
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+
+    /// Advance the generator as if `n` outputs were drawn, in `O(log n)` steps.
+    pub fn jump(&mut self, mut n: u64) {
+        self.draws += n;
+        // the step is the affine map `s -> a * s + b`, compose it n times by squaring
+        let (mut a, mut b) = (Self::MULTIPLIER, Self::INCREMENT.wrapping_neg());
+        let (mut acc_a, mut acc_b) = (1u128, 0u128);
+        while n > 0 {
+            if n & 1 == 1 {
+                acc_a = acc_a.wrapping_mul(a);
+                acc_b = acc_b.wrapping_mul(a).wrapping_add(b);
+            }
+            b = b.wrapping_mul(a).wrapping_add(b);
+            a = a.wrapping_mul(a);
+            n >>= 1;
         }
+        self.state = self.state.wrapping_mul(acc_a).wrapping_add(acc_b);
     }
 
     fn next(&mut self) -> u128 {
+        self.draws += 1;
         self.state = self
             .state
-            .wrapping_mul(self.a as u128)
-            .wrapping_sub(self.c as u128);
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_sub(Self::INCREMENT);
         self.state
     }
 
+    /// Get the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        (self.next() >> 64) as u64
+    }
+
+    /// Get a random float in `[0, 1)`, using the upper 53 bits of the next output.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Fill `buf` with random bytes, taking eight bytes from every output.
+    pub fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
     pub fn generate_range(&mut self, range: usize) -> usize {
-        ((self.next() >> 64) % range as u128) as usize
+        (self.next_u64() as u128 % range as u128) as usize
+    }
+
+    /// Shuffle `items` in place with the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.generate_range(i + 1));
+        }
+    }
+}
+
+/// Seeds the generator from the clock, or with `0` without the `std` feature.
+impl Default for Lcg {
+    #[cfg(feature = "std")]
+    fn default() -> Self {
+        Self::new()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn default() -> Self {
+        Self::new_seed(0)
     }
 }
 
@@ -51,3 +150,1056 @@ fn test_lcg_generate_range() {
 
     assert!(next < 10);
 }
+
+#[test]
+fn test_lcg_fill_bytes() {
+    let mut first = [0u8; 21];
+    let mut second = [0u8; 21];
+    Lcg::new_seed(99).fill_bytes(&mut first);
+    Lcg::new_seed(99).fill_bytes(&mut second);
+
+    assert_eq!(first, second);
+    assert!(first.iter().any(|b| *b != 0));
+}
+
+#[test]
+fn test_lcg_next_f64() {
+    let mut lcg = Lcg::with_seed(9);
+    for _ in 0..1000 {
+        let x = lcg.next_f64();
+        assert!((0. ..1.).contains(&x));
+    }
+}
+
+#[test]
+fn test_lcg_jump() {
+    let mut stepped = Lcg::with_seed(17);
+    for _ in 0..1234 {
+        stepped.next_u64();
+    }
+    let mut jumped = Lcg::with_seed(17);
+    jumped.jump(1234);
+    assert_eq!(jumped.draws(), 1234);
+    assert_eq!(stepped.draws(), 1234);
+    assert_eq!(jumped.next_u64(), stepped.next_u64());
+}
+
+#[test]
+fn test_lcg_reseed() {
+    let mut rng = Lcg::with_seed(3);
+    rng.jump(50);
+    rng.next_u64();
+    rng.reseed(29);
+    assert_eq!(rng.draws(), 0);
+
+    let mut fresh = Lcg::with_seed(29);
+    for _ in 0..10 {
+        assert_eq!(rng.next_u64(), fresh.next_u64());
+    }
+}
+
+#[test]
+fn test_lcg_warm_up() {
+    let mut discarded = Lcg::with_seed(5);
+    for _ in 0..8 {
+        discarded.next_u64();
+    }
+    let mut warmed = Lcg::with_seed_warmed(5, 8);
+    assert_eq!(warmed.draws(), 8);
+    for _ in 0..10 {
+        assert_eq!(warmed.next_u64(), discarded.next_u64());
+    }
+
+    // the first output of a small seed only has low bits set
+    assert_eq!(Lcg::with_seed(5).next_u64(), 0);
+    assert_ne!(Lcg::with_seed_warmed(5, 8).next_u64() >> 32, 0);
+}
+
+#[test]
+fn test_lcg_shuffle() {
+    let mut items = (0..20).collect::<Vec<u32>>();
+    Lcg::with_seed(3).shuffle(&mut items);
+    assert_ne!(items, (0..20).collect::<Vec<_>>());
+
+    let mut other = (0..20).collect::<Vec<u32>>();
+    Lcg::with_seed(3).shuffle(&mut other);
+    assert_eq!(items, other);
+    other.sort_unstable();
+    assert_eq!(other, (0..20).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_lcg_recurrence() {
+    let seed = 123456789u128;
+    let expected = (seed.wrapping_mul(Lcg::MULTIPLIER).wrapping_sub(Lcg::INCREMENT) >> 64) as u64;
+    assert_eq!(Lcg::new_seed(seed).next_u64(), expected);
+}
+
+/// Samples `k` edges uniformly from a stream of edges, without collecting the whole stream.
+/// If the stream has at most `k` edges, all of them are returned.
+pub fn reservoir_sample<I: Iterator<Item = (u32, u32)>>(
+    iter: I,
+    k: usize,
+    rng: &mut Lcg,
+) -> Vec<(u32, u32)> {
+    let mut reservoir = Vec::with_capacity(k);
+    for (i, edge) in iter.enumerate() {
+        if i < k {
+            reservoir.push(edge);
+        } else {
+            let j = rng.generate_range(i + 1);
+            if j < k {
+                reservoir[j] = edge;
+            }
+        }
+    }
+    reservoir
+}
+
+/// Selects every node in `0..n_nodes` with probability `fraction` and keeps the edges
+/// between the selected nodes. The node ids are not changed.
+pub fn sample_induced_subgraph(
+    edges: &[(u32, u32)],
+    n_nodes: usize,
+    fraction: f64,
+    rng: &mut Lcg,
+) -> Vec<(u32, u32)> {
+    let selected = (0..n_nodes)
+        .map(|_| rng.next_f64() < fraction)
+        .collect::<Vec<_>>();
+    let is_selected = |node: u32| selected.get(node as usize).copied().unwrap_or(false);
+    edges
+        .iter()
+        .copied()
+        .filter(|(tail, head)| is_selected(*tail) && is_selected(*head))
+        .collect()
+}
+
+/// Removes every edge with probability `drop_prob`, keeping the order of the other edges.
+pub fn dropout_edges(edges: &[(u32, u32)], drop_prob: f64, rng: &mut Lcg) -> Vec<(u32, u32)> {
+    edges
+        .iter()
+        .copied()
+        .filter(|_| rng.next_f64() >= drop_prob)
+        .collect()
+}
+
+/// Splits the edges randomly into a train and a test set, returned as `(train, test)`.
+///
+/// The test set contains `round(test_fraction * edges.len())` edges, chosen uniformly.
+/// Both sets keep the order of the edges.
+#[allow(clippy::type_complexity)]
+pub fn split_edges(
+    edges: &[(u32, u32)],
+    test_fraction: f64,
+    rng: &mut Lcg,
+) -> (Vec<(u32, u32)>, Vec<(u32, u32)>) {
+    // `as` saturates negative values to 0, so adding 0.5 rounds
+    let n_test = ((test_fraction * edges.len() as f64 + 0.5) as usize).min(edges.len());
+    let mut indices = (0..edges.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut indices);
+    let mut is_test = vec![false; edges.len()];
+    indices[..n_test].iter().for_each(|i| is_test[*i] = true);
+
+    let (test, train): (Vec<_>, Vec<_>) = edges
+        .iter()
+        .zip(is_test)
+        .partition(|(_, is_test)| *is_test);
+    let edges_of = |set: Vec<(&(u32, u32), bool)>| set.into_iter().map(|(e, _)| *e).collect();
+    (edges_of(train), edges_of(test))
+}
+
+/// Decodes a Prüfer sequence into the edges of the tree on `seq.len() + 2` nodes it describes.
+///
+/// The edges are undirected, every edge is given as `(leaf, neighbor)` in the order in which
+/// the leaves are removed. All entries of `seq` have to be smaller than `seq.len() + 2`.
+pub fn prufer_to_tree(seq: &[u32]) -> Vec<(u32, u32)> {
+    let n = seq.len() + 2;
+    let mut degrees = vec![1usize; n];
+    seq.iter().for_each(|node| degrees[*node as usize] += 1);
+
+    let mut leaves = (0..n as u32)
+        .filter(|node| degrees[*node as usize] == 1)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut edges = Vec::with_capacity(n - 1);
+    for &node in seq {
+        let Reverse(leaf) = leaves.pop().unwrap();
+        edges.push((leaf, node));
+        degrees[node as usize] -= 1;
+        if degrees[node as usize] == 1 {
+            leaves.push(Reverse(node));
+        }
+    }
+
+    // the last two leaves are connected to each other
+    let Reverse(first) = leaves.pop().unwrap();
+    let Reverse(second) = leaves.pop().unwrap();
+    edges.push((first, second));
+    edges
+}
+
+/// Encodes a tree on `n` nodes into its Prüfer sequence of length `n - 2`.
+/// The direction of the edges is ignored.
+///
+/// Returns `None` if the edges do not form a tree on the nodes `0..n`.
+pub fn tree_to_prufer(edges: &[(u32, u32)], n: u32) -> Option<Vec<u32>> {
+    let n = n as usize;
+    if n < 2
+        || edges.len() != n - 1
+        || edges.iter().any(|(tail, head)| *tail.max(head) as usize >= n)
+        || connected_components(edges, n).len() != 1
+    {
+        return None;
+    }
+
+    let mut neighbors = vec![BTreeSet::new(); n];
+    for &(tail, head) in edges {
+        neighbors[tail as usize].insert(head);
+        neighbors[head as usize].insert(tail);
+    }
+
+    let mut leaves = (0..n as u32)
+        .filter(|node| neighbors[*node as usize].len() == 1)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut seq = Vec::with_capacity(n - 2);
+    for _ in 0..n - 2 {
+        let Reverse(leaf) = leaves.pop()?;
+        let node = *neighbors[leaf as usize].iter().next()?;
+        neighbors[node as usize].remove(&leaf);
+        seq.push(node);
+        if neighbors[node as usize].len() == 1 {
+            leaves.push(Reverse(node));
+        }
+    }
+
+    Some(seq)
+}
+
+/// An edge whose ends can be used by the analysis functions, like [`topological_sort`].
+///
+/// Implemented for plain `(tail, head)` edges and weighted `(tail, head, weight)` edges,
+/// whose weight is ignored.
+pub trait AsEdge {
+    /// The ends `(tail, head)` of the edge.
+    fn as_edge(&self) -> (u32, u32);
+}
+
+impl AsEdge for (u32, u32) {
+    fn as_edge(&self) -> (u32, u32) {
+        *self
+    }
+}
+
+impl AsEdge for (u32, u32, f64) {
+    fn as_edge(&self) -> (u32, u32) {
+        (self.0, self.1)
+    }
+}
+
+/// Sorts the nodes of a graph topologically, so that every tail comes before its head.
+/// Returns `None` if the edges contain a cycle.
+/// All nodes referenced by `edges` have to be smaller than `n_nodes`.
+pub fn topological_sort<E: AsEdge>(edges: &[E], n_nodes: usize) -> Option<Vec<u32>> {
+    let mut successors = vec![Vec::new(); n_nodes];
+    let mut in_degree = vec![0usize; n_nodes];
+    for (tail, head) in edges.iter().map(AsEdge::as_edge) {
+        successors[tail as usize].push(head);
+        in_degree[head as usize] += 1;
+    }
+
+    // start with all sources, in ascending order
+    let mut frontier = (0..n_nodes as u32)
+        .filter(|n| in_degree[*n as usize] == 0)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(n_nodes);
+
+    while let Some(node) = frontier.pop_front() {
+        order.push(node);
+        for &successor in &successors[node as usize] {
+            in_degree[successor as usize] -= 1;
+            if in_degree[successor as usize] == 0 {
+                frontier.push_back(successor);
+            }
+        }
+    }
+
+    // if not all nodes could be ordered, there is a cycle
+    if order.len() == n_nodes {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+/// Sorts the nodes of a graph in reverse dependency order, so that sinks come first.
+/// This is the reverse of [`topological_sort`].
+pub fn reverse_topological<E: AsEdge>(edges: &[E], n_nodes: usize) -> Option<Vec<u32>> {
+    topological_sort(edges, n_nodes).map(|mut order| {
+        order.reverse();
+        order
+    })
+}
+
+/// Checks if the graph contains no cycle.
+pub fn is_acyclic<E: AsEdge>(edges: &[E], n_nodes: usize) -> bool {
+    topological_sort(edges, n_nodes).is_some()
+}
+
+/// Builds a map from every node to its successors, in the order of the edges.
+/// Nodes without outgoing edges are not contained.
+#[cfg(feature = "std")]
+pub fn build_adjacency(edges: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+    let mut successors: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(tail, head) in edges {
+        successors.entry(tail).or_default().push(head);
+    }
+    successors
+}
+
+/// Builds a map from every node to its predecessors, in the order of the edges.
+/// Nodes without incoming edges are not contained.
+#[cfg(feature = "std")]
+pub fn build_in_adjacency(edges: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+    let mut predecessors: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &(tail, head) in edges {
+        predecessors.entry(head).or_default().push(tail);
+    }
+    predecessors
+}
+
+/// A graph stored in compressed sparse row format, where the successors of all nodes
+/// are stored in one contiguous array.
+pub struct CompactGraph {
+    /// The successors of node `i` are `targets[offsets[i]..offsets[i + 1]]`.
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+}
+
+impl CompactGraph {
+    /// Create the graph from its edges, the successors of every node keep the order of the edges.
+    pub fn from_edges(edges: &[(u32, u32)], n_nodes: usize) -> Self {
+        let mut offsets = vec![0; n_nodes + 1];
+        edges.iter().for_each(|(tail, _)| offsets[*tail as usize + 1] += 1);
+        for i in 0..n_nodes {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut targets = vec![0; edges.len()];
+        for &(tail, head) in edges {
+            targets[next[tail as usize]] = head;
+            next[tail as usize] += 1;
+        }
+
+        Self { offsets, targets }
+    }
+
+    pub fn n_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn successors(&self, node: u32) -> &[u32] {
+        &self.targets[self.offsets[node as usize]..self.offsets[node as usize + 1]]
+    }
+
+    /// Calculates the distance of every node from `src`, in number of edges.
+    /// Nodes which can not be reached from `src` have no distance.
+    pub fn bfs(&self, src: u32) -> Vec<Option<u32>> {
+        let mut distances = vec![None; self.n_nodes()];
+        distances[src as usize] = Some(0);
+        let mut queue = VecDeque::from([src]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node as usize].map(|d| d + 1);
+            for &successor in self.successors(node) {
+                if distances[successor as usize].is_none() {
+                    distances[successor as usize] = distance;
+                    queue.push_back(successor);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+/// Checks if there is a path from `from` to `to`.
+#[cfg(feature = "std")]
+pub fn is_reachable(edges: &[(u32, u32)], from: u32, to: u32) -> bool {
+    let successors = build_adjacency(edges);
+
+    let mut visited = HashSet::from([from]);
+    let mut frontier = vec![from];
+    while let Some(node) = frontier.pop() {
+        if node == to {
+            return true;
+        }
+        for &successor in successors.get(&node).into_iter().flatten() {
+            if visited.insert(successor) {
+                frontier.push(successor);
+            }
+        }
+    }
+
+    false
+}
+
+/// Finds a path from `src` to `dst` with the least number of edges, using a breadth first
+/// search. The path starts with `src` and ends with `dst`.
+///
+/// Returns `None` if `dst` can not be reached from `src`.
+pub fn shortest_path(edges: &[(u32, u32)], src: u32, dst: u32) -> Option<Vec<u32>> {
+    let mut successors: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for &(tail, head) in edges {
+        successors.entry(tail).or_default().push(head);
+    }
+
+    let mut parents = BTreeMap::from([(src, src)]);
+    let mut queue = VecDeque::from([src]);
+    while let Some(node) = queue.pop_front() {
+        if node == dst {
+            let mut path = vec![dst];
+            let mut node = dst;
+            while node != src {
+                node = parents[&node];
+                path.push(node);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &successor in successors.get(&node).into_iter().flatten() {
+            if let alloc::collections::btree_map::Entry::Vacant(entry) = parents.entry(successor) {
+                entry.insert(node);
+                queue.push_back(successor);
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the weakly connected components of a graph, every node without edges
+/// forms a component of its own.
+///
+/// Components are ordered by their lowest node, the nodes of a component are ascending.
+pub fn connected_components<E: AsEdge>(edges: &[E], n_nodes: usize) -> Vec<Vec<u32>> {
+    // union find, with path halving
+    let mut parents = (0..n_nodes as u32).collect::<Vec<_>>();
+    fn find(parents: &mut [u32], mut node: u32) -> u32 {
+        while parents[node as usize] != node {
+            parents[node as usize] = parents[parents[node as usize] as usize];
+            node = parents[node as usize];
+        }
+        node
+    }
+
+    for (tail, head) in edges.iter().map(AsEdge::as_edge) {
+        let (tail, head) = (find(&mut parents, tail), find(&mut parents, head));
+        parents[tail.max(head) as usize] = tail.min(head);
+    }
+
+    let mut components: Vec<Vec<u32>> = Vec::new();
+    let mut component_of_root = BTreeMap::new();
+    for node in 0..n_nodes as u32 {
+        let root = find(&mut parents, node);
+        let index = *component_of_root.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[index].push(node);
+    }
+
+    components
+}
+
+/// Calculates the number of outgoing edges of every node.
+pub fn out_degrees<E: AsEdge>(edges: &[E], n_nodes: usize) -> Vec<usize> {
+    let mut degrees = vec![0; n_nodes];
+    edges.iter().for_each(|edge| degrees[edge.as_edge().0 as usize] += 1);
+    degrees
+}
+
+/// Calculates the number of incoming edges of every node.
+pub fn in_degrees<E: AsEdge>(edges: &[E], n_nodes: usize) -> Vec<usize> {
+    let mut degrees = vec![0; n_nodes];
+    edges.iter().for_each(|edge| degrees[edge.as_edge().1 as usize] += 1);
+    degrees
+}
+
+/// Counts the nodes by their degree, counting both incoming and outgoing edges.
+/// The entry at index `d` is the number of nodes with degree `d`, up to the highest degree.
+pub fn degree_histogram<E: AsEdge>(edges: &[E], n_nodes: usize) -> Vec<usize> {
+    let mut degrees = out_degrees(edges, n_nodes);
+    degrees
+        .iter_mut()
+        .zip(in_degrees(edges, n_nodes))
+        .for_each(|(degree, in_degree)| *degree += in_degree);
+
+    let mut histogram = vec![0; degrees.iter().max().map_or(0, |max| max + 1)];
+    degrees.iter().for_each(|degree| histogram[*degree] += 1);
+    histogram
+}
+
+/// Calculates the average degree `2 * edges / n_nodes`, counting every edge once for both
+/// of its ends, which is the same as the average degree of the undirected graph.
+///
+/// A graph without nodes has an average degree of `0`.
+pub fn average_degree(edges: &[(u32, u32)], n_nodes: usize) -> f64 {
+    if n_nodes == 0 {
+        return 0.;
+    }
+    2. * edges.len() as f64 / n_nodes as f64
+}
+
+/// Adds random edges to an acyclic graph until its [`average_degree`] reaches `target_avg`,
+/// or until the graph is complete.
+///
+/// New edges always point forward in a topological order of `edges`, so the graph stays
+/// acyclic, and they never duplicate an existing edge. They are appended after `edges`.
+/// If `edges` contain a cycle, they are returned unchanged.
+pub fn densify_to_degree(
+    mut edges: Vec<(u32, u32)>,
+    n_nodes: usize,
+    target_avg: f64,
+    seed: u64,
+) -> Vec<(u32, u32)> {
+    let Some(order) = topological_sort(&edges, n_nodes) else {
+        return edges;
+    };
+    let mut existing = edges.iter().copied().collect::<BTreeSet<_>>();
+    let max_edges = n_nodes * n_nodes.saturating_sub(1) / 2;
+    let mut rng = Lcg::with_seed(seed);
+
+    while average_degree(&edges, n_nodes) < target_avg && existing.len() < max_edges {
+        let (a, b) = (rng.generate_range(n_nodes), rng.generate_range(n_nodes));
+        if a == b {
+            continue;
+        }
+        let edge = (order[a.min(b)], order[a.max(b)]);
+        if existing.insert(edge) {
+            edges.push(edge);
+        }
+    }
+    edges
+}
+
+/// Removes duplicate edges, keeping the first occurrence of every edge in its original order.
+pub fn dedup_edges(edges: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut seen = BTreeSet::new();
+    edges.iter().copied().filter(|edge| seen.insert(*edge)).collect()
+}
+
+/// Builds the incidence matrix of the graph, with a row for every node and a column for every
+/// edge. Column `i` is `-1` at the tail and `+1` at the head of `edges[i]`, and `0` elsewhere,
+/// so the column of a loop is all `0`.
+///
+/// The matrix is dense and needs `n_nodes * edges.len()` bytes.
+pub fn to_incidence_matrix(edges: &[(u32, u32)], n_nodes: usize) -> Vec<Vec<i8>> {
+    let mut matrix = vec![vec![0; edges.len()]; n_nodes];
+    for (i, &(tail, head)) in edges.iter().enumerate() {
+        matrix[tail as usize][i] -= 1;
+        matrix[head as usize][i] += 1;
+    }
+    matrix
+}
+
+/// Creates the edges of a dense adjacency matrix, like the one built by
+/// [`to_adjacency_matrix`](crate::layered::LayeredGraph::to_adjacency_matrix).
+/// There is an edge `(i, j)` for every nonzero `matrix[i][j]`, in row major order.
+pub fn from_adjacency_matrix(matrix: &[Vec<u8>]) -> Vec<(u32, u32)> {
+    matrix
+        .iter()
+        .enumerate()
+        .flat_map(|(tail, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, entry)| **entry != 0)
+                .map(move |(head, _)| (tail as u32, head as u32))
+        })
+        .collect()
+}
+
+/// Collapses parallel edges into a single edge, weighted by the number of times it occurs.
+/// The edges are sorted.
+pub fn collapse_parallel(edges: &[(u32, u32)]) -> Vec<(u32, u32, u32)> {
+    let mut multiplicities = BTreeMap::new();
+    for edge in edges {
+        *multiplicities.entry(*edge).or_insert(0) += 1;
+    }
+    multiplicities
+        .into_iter()
+        .map(|((tail, head), count)| (tail, head, count))
+        .collect()
+}
+
+/// Relabels the nodes in the order they are discovered by a breadth first search from `start`,
+/// so `start` becomes node `0`. The direction of the edges is ignored for the search and the
+/// neighbors of a node are visited in the order of the edges.
+///
+/// Nodes which can not be reached from `start` are relabeled afterwards, by searching from
+/// the first node of the edges which has not been discovered yet.
+/// The edges keep their order and direction.
+pub fn relabel_bfs(edges: &[(u32, u32)], start: u32) -> Vec<(u32, u32)> {
+    let mut neighbors: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for &(tail, head) in edges {
+        neighbors.entry(tail).or_default().push(head);
+        neighbors.entry(head).or_default().push(tail);
+    }
+
+    let mut new_ids = BTreeMap::new();
+    let roots = core::iter::once(start).chain(edges.iter().map(|(tail, _)| *tail));
+    for root in roots {
+        if new_ids.contains_key(&root) {
+            continue;
+        }
+        new_ids.insert(root, new_ids.len() as u32);
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            for &neighbor in neighbors.get(&node).into_iter().flatten() {
+                if !new_ids.contains_key(&neighbor) {
+                    new_ids.insert(neighbor, new_ids.len() as u32);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .map(|(tail, head)| (new_ids[tail], new_ids[head]))
+        .collect()
+}
+
+/// Creates the line graph, which has a node for every edge, where node `i` is edge `edges[i]`.
+/// Two nodes are connected if their edges share an end, ignoring the direction of the edges.
+///
+/// Every pair of adjacent nodes is connected once, from the lower to the higher index.
+/// The result is sorted.
+pub fn line_graph(edges: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut incident: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for (i, &(tail, head)) in edges.iter().enumerate() {
+        incident.entry(tail).or_default().push(i as u32);
+        if head != tail {
+            incident.entry(head).or_default().push(i as u32);
+        }
+    }
+
+    let mut line_edges = BTreeSet::new();
+    for indices in incident.values() {
+        for (n, &i) in indices.iter().enumerate() {
+            for &j in &indices[n + 1..] {
+                line_edges.insert((i, j));
+            }
+        }
+    }
+
+    line_edges.into_iter().collect()
+}
+
+/// Finds the `k` nodes with the highest total degree, counting incoming and outgoing edges.
+///
+/// Returns `(node, degree)` pairs sorted by descending degree, nodes with the same
+/// degree are sorted by ascending id. Nodes without edges are never returned.
+pub fn top_degree_nodes(edges: &[(u32, u32)], k: usize) -> Vec<(u32, u32)> {
+    let mut degrees = BTreeMap::new();
+    for (tail, head) in edges {
+        *degrees.entry(*tail).or_insert(0) += 1;
+        *degrees.entry(*head).or_insert(0) += 1;
+    }
+
+    let mut nodes = degrees.into_iter().collect::<Vec<(u32, u32)>>();
+    nodes.sort_unstable_by_key(|(node, degree)| (Reverse(*degree), *node));
+    nodes.truncate(k);
+    nodes
+}
+
+/// Calculates the layer of every node, which is the length of the longest path leading to it.
+/// Sources are in layer 0.
+/// Returns `None` if the edges contain a cycle.
+pub fn longest_path_layers(edges: &[(u32, u32)], n_nodes: usize) -> Option<Vec<usize>> {
+    let order = topological_sort(edges, n_nodes)?;
+    let mut successors = vec![Vec::new(); n_nodes];
+    for &(tail, head) in edges {
+        successors[tail as usize].push(head);
+    }
+
+    let mut layers = vec![0; n_nodes];
+    for node in order {
+        for &successor in &successors[node as usize] {
+            layers[successor as usize] = layers[successor as usize].max(layers[node as usize] + 1);
+        }
+    }
+
+    Some(layers)
+}
+
+/// Combines two graphs into one, without connecting them.
+/// The ids of the `right` graph get offset by the number of nodes in the `left` graph.
+pub fn disjoint_union(left: &[(u32, u32)], left_nodes: usize, right: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let offset = left_nodes as u32;
+    left.iter()
+        .copied()
+        .chain(right.iter().map(|(tail, head)| (tail + offset, head + offset)))
+        .collect()
+}
+
+/// Returns the edges unchanged, together with the number of nodes in `0..n_nodes` which
+/// have at least one edge.
+/// Combine it with [`compact_ids`] to also renumber the remaining nodes.
+pub fn without_isolated(edges: &[(u32, u32)], n_nodes: usize) -> (Vec<(u32, u32)>, usize) {
+    let mut connected = vec![false; n_nodes];
+    for &(tail, head) in edges {
+        connected[tail as usize] = true;
+        connected[head as usize] = true;
+    }
+
+    (edges.to_vec(), connected.into_iter().filter(|c| *c).count())
+}
+
+/// Renumbers the nodes of a graph, so that the ids of all nodes with edges are contiguous.
+/// The relative order of the ids is kept.
+///
+/// Returns the renumbered edges, and a map where the entry at index `i` is the old id of node `i`.
+pub fn compact_ids(edges: &[(u32, u32)]) -> (Vec<(u32, u32)>, Vec<u32>) {
+    let mut map = edges
+        .iter()
+        .flat_map(|(tail, head)| [*tail, *head])
+        .collect::<Vec<_>>();
+    map.sort_unstable();
+    map.dedup();
+
+    let new_id = |old: &u32| map.binary_search(old).unwrap() as u32;
+    let edges = edges
+        .iter()
+        .map(|(tail, head)| (new_id(tail), new_id(head)))
+        .collect();
+
+    (edges, map)
+}
+
+#[test]
+fn test_topological_sort() {
+    let edges = [(0, 2), (2, 1), (1, 3), (0, 3)];
+    assert_eq!(topological_sort(&edges, 4), Some(vec![0, 2, 1, 3]));
+    assert_eq!(topological_sort(&[(0, 1), (1, 0)], 2), None);
+}
+
+#[test]
+fn test_weighted_analysis() {
+    let edges = [(0, 2), (2, 1), (1, 3), (0, 3), (5, 4)];
+    let weighted = edges.map(|(tail, head)| (tail, head, 0.5));
+    assert_eq!(topological_sort(&weighted, 6), topological_sort(&edges, 6));
+    assert_eq!(connected_components(&weighted, 6), connected_components(&edges, 6));
+    assert_eq!(degree_histogram(&weighted, 6), degree_histogram(&edges, 6));
+}
+
+#[test]
+fn test_reverse_topological() {
+    let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)];
+    let mut forward = topological_sort(&edges, 6).unwrap();
+    forward.reverse();
+    assert_eq!(reverse_topological(&edges, 6), Some(forward));
+    assert_eq!(reverse_topological(&[(0, 1), (1, 2), (2, 0)], 3), None);
+}
+
+#[test]
+fn test_longest_path_layers() {
+    let edges = [(0, 1), (1, 2), (0, 2), (3, 2)];
+    assert_eq!(longest_path_layers(&edges, 5), Some(vec![0, 1, 2, 0, 0]));
+    assert_eq!(longest_path_layers(&[(0, 1), (1, 0)], 2), None);
+}
+
+#[test]
+fn test_disjoint_union() {
+    let actual = disjoint_union(&[(0, 1)], 3, &[(0, 1), (1, 2)]);
+    assert_eq!(actual, vec![(0, 1), (3, 4), (4, 5)]);
+}
+
+#[test]
+fn test_without_isolated() {
+    let edges = [(0, 2), (2, 4)];
+    assert_eq!(without_isolated(&edges, 5), (edges.to_vec(), 3));
+}
+
+#[test]
+fn test_compact_ids() {
+    let (edges, map) = compact_ids(&[(0, 7), (7, 3)]);
+    assert_eq!(edges, vec![(0, 2), (2, 1)]);
+    assert_eq!(map, vec![0, 3, 7]);
+}
+
+#[test]
+fn test_degrees() {
+    let edges = [(0, 1), (0, 2), (1, 2)];
+    assert_eq!(out_degrees(&edges, 4), vec![2, 1, 0, 0]);
+    assert_eq!(in_degrees(&edges, 4), vec![0, 1, 2, 0]);
+    // one node with degree 0 and three with degree 2
+    assert_eq!(degree_histogram(&edges, 4), vec![1, 0, 3]);
+    assert!(degree_histogram::<(u32, u32)>(&[], 0).is_empty());
+}
+
+#[test]
+fn test_average_degree() {
+    let n = 6;
+    let complete = (0..n)
+        .flat_map(|tail| (tail + 1..n).map(move |head| (tail, head)))
+        .collect::<Vec<_>>();
+    assert_eq!(average_degree(&complete, n as usize), (n - 1) as f64);
+
+    let path = (0..n - 1).map(|node| (node, node + 1)).collect::<Vec<_>>();
+    assert_eq!(average_degree(&path, n as usize), 2. * (n - 1) as f64 / n as f64);
+    assert_eq!(average_degree(&[], 0), 0.);
+
+    let edges = crate::random::RandomGraph::from_avg_degree(50, 4., 1).build_edges();
+    assert_eq!(average_degree(&edges, 50), 4.);
+}
+
+#[test]
+fn test_densify_to_degree() {
+    let n = 40;
+    let path = (0..n as u32 - 1).map(|node| (node, node + 1)).collect::<Vec<_>>();
+    let edges = densify_to_degree(path.clone(), n, 6., 3);
+    assert_eq!(edges[..path.len()], path[..]);
+    assert!(average_degree(&edges, n) >= 6.);
+    assert!(average_degree(&edges[..edges.len() - 1], n) < 6.);
+    assert!(is_acyclic(&edges, n));
+    assert_eq!(dedup_edges(&edges), edges);
+    assert_eq!(edges, densify_to_degree(path.clone(), n, 6., 3));
+
+    // the target is out of reach, so the graph becomes complete
+    let complete = densify_to_degree(path[..4].to_vec(), 5, 10., 3);
+    assert_eq!(complete.len(), 10);
+    assert!(is_acyclic(&complete, 5));
+
+    // already dense enough or cyclic graphs don't change
+    assert_eq!(densify_to_degree(path.clone(), n, 1., 3), path);
+    assert_eq!(densify_to_degree(vec![(0, 1), (1, 0)], 3, 4., 3), vec![(0, 1), (1, 0)]);
+}
+
+#[test]
+fn test_dedup_edges() {
+    let edges = [(3, 1), (0, 2), (3, 1), (1, 0), (0, 2), (2, 3)];
+    assert_eq!(dedup_edges(&edges), vec![(3, 1), (0, 2), (1, 0), (2, 3)]);
+    assert!(dedup_edges(&[]).is_empty());
+}
+
+#[test]
+fn test_to_incidence_matrix() {
+    let matrix = to_incidence_matrix(&crate::topology::path_graph(3), 3);
+    assert_eq!(matrix, vec![vec![-1, 0], vec![1, -1], vec![0, 1]]);
+    assert_eq!(to_incidence_matrix(&[(1, 1)], 2), vec![vec![0], vec![0]]);
+}
+
+#[test]
+fn test_from_adjacency_matrix() {
+    use crate::layered::LayeredGraph;
+    use std::collections::HashSet;
+    let layout = LayeredGraph::new_from_num_nodes(22, 2);
+    let edges = from_adjacency_matrix(&layout.to_adjacency_matrix());
+    assert_eq!(
+        edges.into_iter().collect::<HashSet<_>>(),
+        layout.build_edges().into_iter().collect::<HashSet<_>>()
+    );
+
+    let matrix = vec![vec![0, 2, 0], vec![1, 0, 0], vec![0, 0, 1]];
+    assert_eq!(from_adjacency_matrix(&matrix), vec![(0, 1), (1, 0), (2, 2)]);
+}
+
+#[test]
+fn test_collapse_parallel() {
+    let edges = [(3, 1), (0, 2), (3, 1), (1, 0), (3, 1), (1, 3)];
+    assert_eq!(
+        collapse_parallel(&edges),
+        vec![(0, 2, 1), (1, 0, 1), (1, 3, 1), (3, 1, 3)]
+    );
+    assert!(collapse_parallel(&[]).is_empty());
+}
+
+#[test]
+fn test_relabel_bfs() {
+    let edges = [(0, 1), (1, 2), (1, 3), (4, 5)];
+    // the same graph with 0 -> 3, 1 -> 0, 2 -> 1, 3 -> 2, 4 -> 9, 5 -> 7
+    let relabeled = [(3, 0), (0, 1), (0, 2), (9, 7)];
+
+    let expected = vec![(0, 1), (1, 2), (1, 3), (4, 5)];
+    assert_eq!(relabel_bfs(&edges, 0), expected);
+    assert_eq!(relabel_bfs(&relabeled, 3), expected);
+    assert_eq!(relabel_bfs(&edges, 2), vec![(2, 1), (1, 0), (1, 3), (4, 5)]);
+}
+
+#[test]
+fn test_line_graph() {
+    assert_eq!(line_graph(&[(0, 1), (1, 2)]), vec![(0, 1)]);
+    // a star becomes a complete graph
+    assert_eq!(line_graph(&[(0, 1), (0, 2), (0, 3)]), vec![(0, 1), (0, 2), (1, 2)]);
+    // edges sharing both ends are only connected once
+    assert_eq!(line_graph(&[(0, 1), (1, 0), (2, 3)]), vec![(0, 1)]);
+}
+
+#[test]
+fn test_top_degree_nodes() {
+    let edges = crate::topology::star_graph(6);
+    let top = top_degree_nodes(&edges, 2);
+    assert_eq!(top, vec![(0, 5), (1, 1)]);
+    assert_eq!(top_degree_nodes(&edges, 100).len(), 6);
+}
+
+#[test]
+fn test_compact_graph_bfs() {
+    let path = (0..5).map(|node| (node, node + 1)).collect::<Vec<_>>();
+    let graph = CompactGraph::from_edges(&path, 7);
+    assert_eq!(graph.successors(2), &[3]);
+    assert_eq!(
+        graph.bfs(0),
+        vec![Some(0), Some(1), Some(2), Some(3), Some(4), Some(5), None]
+    );
+    assert_eq!(graph.bfs(3)[..3], [None, None, None]);
+
+    let graph = CompactGraph::from_edges(&[(0, 2), (1, 3), (0, 1), (2, 3)], 4);
+    assert_eq!(graph.successors(0), &[2, 1]);
+    assert_eq!(graph.bfs(0), vec![Some(0), Some(1), Some(1), Some(2)]);
+}
+
+#[test]
+fn test_is_reachable() {
+    let edges = [(0, 1), (1, 2), (3, 2)];
+    assert!(is_reachable(&edges, 0, 2));
+    assert!(is_reachable(&edges, 1, 1));
+    assert!(!is_reachable(&edges, 0, 3));
+    assert!(!is_reachable(&edges, 2, 0));
+}
+
+#[test]
+fn test_shortest_path() {
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 4), (1, 5), (5, 4), (6, 4)];
+    assert_eq!(shortest_path(&edges, 0, 4), Some(vec![0, 1, 5, 4]));
+    assert_eq!(shortest_path(&edges, 2, 2), Some(vec![2]));
+    assert_eq!(shortest_path(&edges, 0, 6), None);
+    assert_eq!(shortest_path(&edges, 4, 0), None);
+
+    use crate::comm::CubeGraph;
+    let edges = CubeGraph::new(3, 3, 3, 3)
+        .build()
+        .into_iter()
+        .map(|(t, h)| (t as u32, h as u32))
+        .collect::<Vec<_>>();
+    // the same corner two timesteps later is reached through a neighbor in between,
+    // which is shorter than going through the two comm vertices
+    let path = shortest_path(&edges, 0, 2 * 27).unwrap();
+    assert_eq!(path.len(), 3);
+    assert!(path.windows(2).all(|edge| edges.contains(&(edge[0], edge[1]))));
+}
+
+#[test]
+fn test_is_reachable_cube_graph() {
+    use crate::comm::CubeGraph;
+    let edges = CubeGraph::new(3, 3, 3, 3)
+        .build()
+        .into_iter()
+        .map(|(t, h)| (t as u32, h as u32))
+        .collect::<Vec<_>>();
+    // the same cell, two timesteps later
+    assert!(is_reachable(&edges, 13, 13 + 2 * 27));
+    assert!(!is_reachable(&edges, 13 + 27, 13));
+}
+
+#[test]
+fn test_connected_components() {
+    let edges = [(0, 1), (3, 1), (2, 4)];
+    assert_eq!(
+        connected_components(&edges, 6),
+        vec![vec![0, 1, 3], vec![2, 4], vec![5]]
+    );
+}
+
+#[test]
+fn test_build_adjacency() {
+    let edges = [(0, 1), (0, 2), (2, 1), (3, 1)];
+    let successors = build_adjacency(&edges);
+    assert_eq!(successors[&0], vec![1, 2]);
+    assert_eq!(successors[&2], vec![1]);
+    assert!(!successors.contains_key(&1));
+
+    let predecessors = build_in_adjacency(&edges);
+    assert_eq!(predecessors[&1], vec![0, 2, 3]);
+    assert!(!predecessors.contains_key(&0));
+}
+
+#[test]
+fn test_reservoir_sample() {
+    let stream = || (0..100).map(|i| (i, i + 1));
+    let sample = reservoir_sample(stream(), 10, &mut Lcg::new_seed(3));
+    assert_eq!(sample.len(), 10);
+    assert!(sample.iter().all(|(t, h)| *h == t + 1 && *t < 100));
+    assert_eq!(sample, reservoir_sample(stream(), 10, &mut Lcg::new_seed(3)));
+
+    assert_eq!(reservoir_sample(stream().take(5), 10, &mut Lcg::new_seed(3)).len(), 5);
+}
+
+#[test]
+fn test_sample_induced_subgraph() {
+    let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)];
+    assert_eq!(sample_induced_subgraph(&edges, 4, 1., &mut Lcg::new_seed(5)), edges);
+    assert!(sample_induced_subgraph(&edges, 4, 0., &mut Lcg::new_seed(5)).is_empty());
+
+    let edges = (0..50).flat_map(|i| (i + 1..50).map(move |j| (i, j))).collect::<Vec<_>>();
+    let sample = sample_induced_subgraph(&edges, 50, 0.5, &mut Lcg::new_seed(5));
+    assert!(!sample.is_empty() && sample.len() < edges.len());
+    assert_eq!(sample, sample_induced_subgraph(&edges, 50, 0.5, &mut Lcg::new_seed(5)));
+}
+
+#[test]
+fn test_dropout_edges() {
+    let edges = (0..100).map(|i| (i, i + 1)).collect::<Vec<_>>();
+    assert_eq!(dropout_edges(&edges, 0., &mut Lcg::new_seed(4)), edges);
+    assert!(dropout_edges(&edges, 1., &mut Lcg::new_seed(4)).is_empty());
+
+    let kept = dropout_edges(&edges, 0.3, &mut Lcg::new_seed(4));
+    assert!(kept.len() > 50 && kept.len() < 90);
+    assert!(kept.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(kept, dropout_edges(&edges, 0.3, &mut Lcg::new_seed(4)));
+}
+
+#[test]
+fn test_split_edges() {
+    use std::collections::HashSet;
+    let edges = (0..40).map(|i| (i, i + 1)).collect::<Vec<_>>();
+    let (train, test) = split_edges(&edges, 0.25, &mut Lcg::new_seed(8));
+    assert_eq!((train.len(), test.len()), (30, 10));
+    let train_set = train.iter().collect::<HashSet<_>>();
+    assert!(test.iter().all(|edge| !train_set.contains(edge)));
+    let mut union = [train.clone(), test.clone()].concat();
+    union.sort_unstable();
+    assert_eq!(union, edges);
+    assert_eq!((train, test), split_edges(&edges, 0.25, &mut Lcg::new_seed(8)));
+
+    assert_eq!(split_edges(&edges[..3], 0.5, &mut Lcg::new_seed(8)).1.len(), 2);
+    assert!(split_edges(&edges, 0., &mut Lcg::new_seed(8)).1.is_empty());
+    assert!(split_edges(&edges, 1., &mut Lcg::new_seed(8)).0.is_empty());
+}
+
+#[test]
+fn test_prufer() {
+    let tree = [(0, 3), (1, 3), (2, 3), (3, 4), (4, 5)];
+    let seq = tree_to_prufer(&tree, 6).unwrap();
+    assert_eq!(seq, vec![3, 3, 3, 4]);
+
+    let decoded = prufer_to_tree(&seq);
+    let canonical = |edges: &[(u32, u32)]| {
+        let mut edges = edges.iter().map(|(t, h)| (*t.min(h), *t.max(h))).collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+    };
+    assert_eq!(canonical(&decoded), canonical(&tree));
+    assert_eq!(prufer_to_tree(&[]), vec![(0, 1)]);
+}
+
+#[test]
+fn test_tree_to_prufer_no_tree() {
+    // cycle
+    assert_eq!(tree_to_prufer(&[(0, 1), (1, 2), (2, 0)], 4), None);
+    // disconnected
+    assert_eq!(tree_to_prufer(&[(0, 1), (1, 0), (2, 3)], 4), None);
+    assert_eq!(tree_to_prufer(&[(0, 1)], 3), None);
+}